@@ -50,6 +50,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         "./src/dma/cs2dumper/engine2_mod.rs"
     ).expect("Failed to download build file \"engine2.dll.rs\"");
 
+    download(
+        "https://raw.githubusercontent.com/a2x/cs2-dumper/refs/heads/main/output/interfaces.rs",
+        "./src/dma/cs2dumper/interfaces_mod.rs"
+    ).expect("Failed to download build file \"interfaces.rs\"");
+
     build_number()?;
 
     let gitcl = GitclBuilder::all_git()?;
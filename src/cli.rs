@@ -1,14 +1,17 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use memflow::plugins::Inventory;
 
-use crate::dma::Connector;
+use crate::{dma::Connector, enums::{Perspective, RoundPhase, TeamID}, render};
 const PORT_RANGE: std::ops::RangeInclusive<usize> = 8000..=65535;
 
 #[derive(Parser, Clone)]
 #[command(author, version = version(), about, long_about = None)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Specifies the connector type for DMA
     #[clap(value_enum, short, long, ignore_case = true, default_value_t = Connector::Qemu)]
     pub connector: Connector,
@@ -32,6 +35,138 @@ pub struct Cli {
     /// Skip the dwBuildNumber check, allows for running with *possibly* outdated offsets.
     #[arg(long)]
     pub skip_version: bool,
+
+    /// Side the radar is viewed from, "ct" or "t" show a fixed side regardless of the local player
+    #[arg(value_enum, long, ignore_case = true, default_value_t = Perspective::Local)]
+    pub perspective: Perspective,
+
+    /// Run player positions through a kalman filter and send filtered and predicted positions
+    #[arg(long)]
+    pub smoothing: bool,
+
+    /// How far ahead predicted positions are extrapolated, in milliseconds
+    #[arg(long, default_value_t = 100)]
+    pub prediction_ms: u32,
+
+    /// Attach a trail of the last N seconds of positions to every player, 0 disables trails
+    #[arg(long, default_value_t = 0.0)]
+    pub trail_seconds: f32,
+
+    /// Only send enemies that are spotted by the viewed team, others show up as last known positions
+    #[arg(long)]
+    pub fog_of_war: bool,
+
+    /// JSON file mapping access tokens to what they may see, clients without a valid token are rejected
+    #[arg(long, value_parser = valid_file)]
+    pub tokens: Option<PathBuf>,
+
+    /// Also send every entity's position projected onto the radar image
+    #[arg(long)]
+    pub radar_pos: bool,
+
+    /// Compute a tactical summary (site counts, zones, nearest players) every tick
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Send a heat layer of where unseen enemies probably are, spread along the nav mesh if there is one
+    #[arg(long)]
+    pub heat: bool,
+
+    /// Append every frame to this file as JSON lines, for `heatmap` and later review
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// Directory holding `<map>.nav` files for path distances, defaults to `<web-path>/assets/nav`
+    #[arg(long, value_parser = valid_path)]
+    pub nav_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// Manage the radar map assets
+    Maps {
+        #[command(subcommand)]
+        command: MapsCommand,
+    },
+
+    /// Render a heatmap of recorded player positions onto the radar image
+    Heatmap {
+        /// Recordings made with --record
+        #[arg(required = true, value_parser = valid_file)]
+        recordings: Vec<PathBuf>,
+
+        /// Map to aggregate, frames on other maps are ignored
+        #[arg(long)]
+        map: String,
+
+        /// Only count players of this side
+        #[arg(value_enum, long, ignore_case = true)]
+        team: Option<TeamID>,
+
+        /// Only count this player
+        #[arg(long)]
+        player: Option<String>,
+
+        /// Only count frames in this round phase
+        #[arg(value_enum, long, ignore_case = true)]
+        phase: Option<RoundPhase>,
+
+        /// Only count this round, 1-based
+        #[arg(long)]
+        round: Option<i32>,
+
+        /// Only count this match, 1-based and numbered across the recordings in the order given
+        #[arg(long)]
+        session: Option<usize>,
+
+        /// Only count players on this floor of a multi-level map, drawn on that floor's image
+        #[arg(long)]
+        layer: Option<String>,
+
+        /// Where the png is written, defaults to heatmap_<map>.png
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Render a recorded round as an animated gif
+    Render {
+        /// Recording made with --record
+        #[arg(value_parser = valid_file)]
+        recording: PathBuf,
+
+        /// 1-based round number
+        #[arg(long)]
+        round: i32,
+
+        /// Which match to take the round from if the recording holds several, 1-based
+        #[arg(long)]
+        session: Option<usize>,
+
+        /// Width and height of the gif in pixels
+        #[arg(long, default_value_t = render::DEFAULT_SIZE)]
+        size: u32,
+
+        /// Frames per second of game time
+        #[arg(long, default_value_t = 10)]
+        fps: u32,
+
+        /// Where the gif is written, defaults to round_<round>.gif
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum MapsCommand {
+    /// Import radar overviews from a CS2 install into the web assets
+    Import {
+        /// CS2 install directory, or any directory containing resource/overviews
+        cs2_dir: PathBuf,
+
+        /// Replace maps that already exist
+        #[arg(long)]
+        overwrite: bool,
+    },
 }
 
 fn version() -> String {
@@ -81,6 +216,16 @@ fn valid_path(s: &str) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+fn valid_file(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+
+    if !path.is_file() {
+        return Err("File does not exist".to_string())
+    }
+
+    Ok(path)
+}
+
 /// Wrapper because log::LevelFilter doesn't implement ValueEnum
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Default)]
 pub enum Loglevel {
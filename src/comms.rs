@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 
-use crate::{structs::Vec3, enums::PlayerType};
+use crate::{economy::EconomyData, estimation::HeatPoint, maps::RadarPos, summary::TacticalSummary};
+
+use crate::{structs::{Vec3, PlayerFlags}, enums::{PlayerType, RoundPhase, MatchPhase, TeamID, Perspective, GameMode, HostageState, BombSite, MarkerKind}};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerData {
@@ -9,6 +11,10 @@ pub struct PlayerData {
     #[serde(rename = "playerType")]
     player_type: PlayerType,
 
+    /// Absolute side of the player, independent of the perspective
+    #[serde(rename = "team", default)]
+    team: Option<TeamID>,
+
     #[serde(rename = "hasBomb")]
     has_bomb: bool,
 
@@ -29,44 +35,364 @@ pub struct PlayerData {
 
     #[serde(rename = "health", default)]
     health: u32,
+
+    /// Set on the player the local player is spectating
+    #[serde(rename = "isObserved", default)]
+    is_observed: bool,
+
+    #[serde(rename = "flags", default)]
+    pub flags: PlayerFlags,
+
+    /// Total duration of the last flash, zero when not flashed
+    #[serde(rename = "flashDuration", default)]
+    pub flash_duration: f32,
+
+    /// Velocity in units per second
+    #[serde(rename = "vel", default)]
+    pub vel: Vec3,
+
+    /// Positions over the last few seconds, oldest first
+    #[serde(rename = "trail", default, skip_serializing_if = "Vec::is_empty")]
+    pub trail: Vec<Vec3>,
+
+    #[serde(rename = "stats", default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<PlayerStats>,
+
+    /// Callout zone the player is in, if the map defines zones
+    #[serde(rename = "zone", default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+
+    /// Floor of a multi-level map the entity is on
+    #[serde(rename = "layer", default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+
+    /// Position on the radar image, only sent if enabled
+    #[serde(rename = "radarPos", default, skip_serializing_if = "Option::is_none")]
+    pub radar_pos: Option<RadarPos>,
+
+    /// Only present with server side smoothing enabled
+    #[serde(rename = "filteredPos", default, skip_serializing_if = "Option::is_none")]
+    pub filtered_pos: Option<Vec3>,
+
+    /// Filtered position extrapolated by the prediction horizon
+    #[serde(rename = "predictedPos", default, skip_serializing_if = "Option::is_none")]
+    pub predicted_pos: Option<Vec3>,
 }
 
 impl PlayerData {
-    pub fn new(pos: Vec3, yaw: f32, player_type: PlayerType, has_bomb: bool, has_awp: bool,
-                    is_scoped: bool, player_name: String, weapon_id: i16, money: i32, health: u32) -> PlayerData {
+    pub fn new(pos: Vec3, yaw: f32, player_type: PlayerType, team: Option<TeamID>, has_bomb: bool, has_awp: bool,
+                    is_scoped: bool, player_name: String, weapon_id: i16, money: i32, health: u32, is_observed: bool) -> PlayerData {
         PlayerData {
             pos,
             yaw,
             player_type,
+            team,
             has_bomb,
             has_awp,
             is_scoped,
             player_name,
             weapon_id,
             money,
-            health
+            health,
+            is_observed,
+            flags: PlayerFlags::default(),
+            flash_duration: 0.0,
+            vel: Vec3::default(),
+            filtered_pos: None,
+            predicted_pos: None,
+            trail: Vec::new(),
+            stats: None,
+            zone: None,
+            layer: None,
+            radar_pos: None
         }
     }
+
+    pub fn pos(&self) -> Vec3 {
+        self.pos
+    }
+
+    pub fn player_type(&self) -> PlayerType {
+        self.player_type
+    }
+
+    pub fn player_name(&self) -> &str {
+        &self.player_name
+    }
+
+    pub fn team(&self) -> Option<TeamID> {
+        self.team
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn has_bomb(&self) -> bool {
+        self.has_bomb
+    }
+
+    pub fn is_observed(&self) -> bool {
+        self.is_observed
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BombData {
     pos: Vec3,
     #[serde(rename = "isPlanted")]
-    is_planted: bool
+    is_planted: bool,
+
+    #[serde(rename = "site", default)]
+    pub site: Option<BombSite>,
+
+    /// Name of the player currently planting
+    #[serde(rename = "planter", default)]
+    pub planter: Option<String>,
+
+    /// Plant progress from 0 to 1, only present while planting
+    #[serde(rename = "plantProgress", default)]
+    pub plant_progress: Option<f32>,
+
+    /// Name of the player currently defusing
+    #[serde(rename = "defuser", default)]
+    pub defuser: Option<String>,
+
+    #[serde(rename = "defuserHasKit", default)]
+    pub defuser_has_kit: bool,
+
+    /// Floor of a multi-level map the entity is on
+    #[serde(rename = "layer", default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+
+    /// Position on the radar image, only sent if enabled
+    #[serde(rename = "radarPos", default, skip_serializing_if = "Option::is_none")]
+    pub radar_pos: Option<RadarPos>,
 }
 
 #[allow(dead_code)]
 impl BombData {
     pub fn new(pos: Vec3, is_planted: bool) -> BombData {
-        BombData { pos, is_planted }
+        BombData {
+            pos,
+            is_planted,
+            site: None,
+            planter: None,
+            plant_progress: None,
+            defuser: None,
+            defuser_has_kit: false,
+            layer: None,
+            radar_pos: None
+        }
+    }
+
+    pub fn is_planted(&self) -> bool {
+        self.is_planted
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostageData {
+    pos: Vec3,
+    state: HostageState,
+
+    /// Name of the player leading the hostage
+    carrier: Option<String>,
+
+    /// Floor of a multi-level map the entity is on
+    #[serde(rename = "layer", default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+
+    /// Position on the radar image, only sent if enabled
+    #[serde(rename = "radarPos", default, skip_serializing_if = "Option::is_none")]
+    pub radar_pos: Option<RadarPos>,
+}
+
+impl HostageData {
+    pub fn new(pos: Vec3, state: HostageState, carrier: Option<String>) -> HostageData {
+        HostageData { pos, state, carrier, layer: None, radar_pos: None }
+    }
+}
+
+/// Scoreboard numbers, read from the controller
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    pub damage: i32,
+    pub mvps: i32,
+
+    #[serde(rename = "headshotPercentage")]
+    pub headshot_percentage: f32,
+
+    pub ping: u32,
+
+    /// Premier rating or competitive skill group, depending on the mode
+    pub rank: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreboardEntry {
+    #[serde(rename = "playerName")]
+    pub player_name: String,
+
+    pub team: Option<TeamID>,
+    pub alive: bool,
+    pub stats: PlayerStats,
+}
+
+/// A player that isn't in the frame anymore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkerData {
+    pos: Vec3,
+    yaw: f32,
+    kind: MarkerKind,
+
+    #[serde(rename = "playerName")]
+    player_name: String,
+
+    #[serde(rename = "playerType")]
+    player_type: PlayerType,
+
+    #[serde(rename = "team", default)]
+    team: Option<TeamID>,
+
+    /// Seconds since the player was last seen
+    age: f32,
+
+    /// Floor of a multi-level map the entity is on
+    #[serde(rename = "layer", default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+
+    /// Position on the radar image, only sent if enabled
+    #[serde(rename = "radarPos", default, skip_serializing_if = "Option::is_none")]
+    pub radar_pos: Option<RadarPos>,
+}
+
+impl MarkerData {
+    pub fn new(pos: Vec3, yaw: f32, kind: MarkerKind, player_name: String, player_type: PlayerType, team: Option<TeamID>, age: f32) -> MarkerData {
+        MarkerData { pos, yaw, kind, player_name, player_type, team, age, layer: None, radar_pos: None }
+    }
+
+    pub fn pos(&self) -> Vec3 {
+        self.pos
+    }
+
+    pub fn kind(&self) -> MarkerKind {
+        self.kind
+    }
+
+    pub fn player_name(&self) -> &str {
+        &self.player_name
+    }
+
+    pub fn player_type(&self) -> PlayerType {
+        self.player_type
+    }
+
+    pub fn age(&self) -> f32 {
+        self.age
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EntityData {
     Player(PlayerData),
-    Bomb(BombData)
+    Bomb(BombData),
+    Hostage(HostageData),
+    Marker(MarkerData)
+}
+
+impl EntityData {
+    pub fn pos(&self) -> Vec3 {
+        match self {
+            EntityData::Player(player) => player.pos,
+            EntityData::Bomb(bomb) => bomb.pos,
+            EntityData::Hostage(hostage) => hostage.pos,
+            EntityData::Marker(marker) => marker.pos,
+        }
+    }
+
+    pub fn set_layer(&mut self, layer: Option<String>) {
+        match self {
+            EntityData::Player(player) => player.layer = layer,
+            EntityData::Bomb(bomb) => bomb.layer = layer,
+            EntityData::Hostage(hostage) => hostage.layer = layer,
+            EntityData::Marker(marker) => marker.layer = layer,
+        }
+    }
+
+    pub fn set_radar_pos(&mut self, radar_pos: Option<RadarPos>) {
+        match self {
+            EntityData::Player(player) => player.radar_pos = radar_pos,
+            EntityData::Bomb(bomb) => bomb.radar_pos = radar_pos,
+            EntityData::Hostage(hostage) => hostage.radar_pos = radar_pos,
+            EntityData::Marker(marker) => marker.radar_pos = radar_pos,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchData {
+    #[serde(rename = "ctScore")]
+    pub ct_score: i32,
+
+    #[serde(rename = "tScore")]
+    pub t_score: i32,
+
+    /// 1-based number of the round currently being played
+    #[serde(rename = "round")]
+    pub round: i32,
+
+    #[serde(rename = "roundPhase")]
+    pub round_phase: RoundPhase,
+
+    /// Seconds left in the current round phase, during freeze time this is the freeze time left
+    #[serde(rename = "roundTimeLeft")]
+    pub round_time_left: f32,
+
+    #[serde(rename = "matchPhase")]
+    pub match_phase: MatchPhase,
+
+    #[serde(rename = "isHalftime")]
+    pub is_halftime: bool,
+
+    /// Number of the overtime currently being played, 0 if in regulation
+    #[serde(rename = "overtime")]
+    pub overtime: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostageObjective {
+    #[serde(rename = "remaining")]
+    pub remaining: i32,
+
+    #[serde(rename = "anyReached")]
+    pub any_reached: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameModeData {
+    #[serde(rename = "gameType")]
+    pub game_type: i32,
+
+    #[serde(rename = "gameMode")]
+    pub game_mode: i32,
+
+    #[serde(rename = "mode")]
+    pub mode: GameMode,
+
+    /// Free for all, everyone but the local player is an enemy
+    #[serde(rename = "ffa")]
+    pub ffa: bool,
+
+    #[serde(rename = "bombTargets")]
+    pub bomb_targets: bool,
+
+    /// Only present on maps with hostage rescue zones
+    #[serde(rename = "hostages")]
+    pub hostages: Option<HostageObjective>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,22 +442,60 @@ pub struct RadarData {
     #[serde(rename = "mapName")]
     map_name: String,
 
-    #[serde(rename(serialize = "entityData"))]
+    #[serde(rename = "match")]
+    match_data: MatchData,
+
+    #[serde(rename = "gameMode")]
+    game_mode: GameModeData,
+
+    #[serde(rename = "entityData")]
     player_data: Vec<EntityData>,
 
+    #[serde(rename = "rescueZones", default)]
+    rescue_zones: Vec<Vec3>,
+
     #[serde(rename = "options")]
     options: CheatOptions,
 
     #[serde(skip)]
     pub money_reveal_enabled: bool,
+
+    #[serde(rename = "perspective", default)]
+    pub perspective: Perspective,
+
+    #[serde(rename = "economy", default)]
+    pub economy: EconomyData,
+
+    /// Only computed if enabled
+    #[serde(rename = "summary", default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<TacticalSummary>,
+
+    /// Where unseen enemies probably are, only computed if enabled
+    #[serde(rename = "heat", default, skip_serializing_if = "Vec::is_empty")]
+    pub heat: Vec<HeatPoint>,
+
+    /// Every player including dead ones, served on /api/scoreboard
+    #[serde(skip)]
+    pub scoreboard: Vec<ScoreboardEntry>,
+
+    /// Server tick this frame was read at
+    #[serde(rename = "tick", default)]
+    pub tick: i32,
+
+    /// Game clock in seconds at the time of the read
+    #[serde(rename = "curTime", default)]
+    pub cur_time: f32,
 }
 
 impl RadarData {
-    pub fn new(ingame: bool, map_name: String, player_data: Vec<EntityData>, freq: usize, bomb_planted: bool, bomb_cannot_defuse: bool, bomb_defuse_timeleft: f32, bomb_exploded: bool, bomb_being_defused: bool, bomb_defuse_length: f32, bomb_defuse_end: f32) -> RadarData {
+    pub fn new(ingame: bool, map_name: String, match_data: MatchData, game_mode: GameModeData, player_data: Vec<EntityData>, rescue_zones: Vec<Vec3>, freq: usize, bomb_planted: bool, bomb_cannot_defuse: bool, bomb_defuse_timeleft: f32, bomb_exploded: bool, bomb_being_defused: bool, bomb_defuse_length: f32, bomb_defuse_end: f32) -> RadarData {
         RadarData {
             ingame,
             map_name,
+            match_data,
+            game_mode,
             player_data,
+            rescue_zones,
             freq,
             bomb_planted,
             bomb_can_defuse: bomb_cannot_defuse,
@@ -141,7 +505,14 @@ impl RadarData {
             bomb_defuse_length,
             bomb_defuse_end,
             options: CheatOptions::default(),
-            money_reveal_enabled: false
+            money_reveal_enabled: false,
+            perspective: Perspective::default(),
+            economy: EconomyData::default(),
+            summary: None,
+            heat: Vec::new(),
+            scoreboard: Vec::new(),
+            tick: 0,
+            cur_time: 0.0
         }
     }
 
@@ -150,7 +521,10 @@ impl RadarData {
         RadarData { 
             ingame: false,
             map_name: String::new(),
+            match_data: MatchData::default(),
+            game_mode: GameModeData::default(),
             player_data: Vec::new(),
+            rescue_zones: Vec::new(),
             freq,
             bomb_planted: false,
             bomb_can_defuse: false,
@@ -160,10 +534,29 @@ impl RadarData {
             bomb_defuse_length: 0.0,
             bomb_defuse_end: 0.0,
             options: CheatOptions::default(),
-            money_reveal_enabled: false
+            money_reveal_enabled: false,
+            perspective: Perspective::default(),
+            economy: EconomyData::default(),
+            summary: None,
+            heat: Vec::new(),
+            scoreboard: Vec::new(),
+            tick: 0,
+            cur_time: 0.0
         }
     }
 
+    pub fn get_map_name(&self) -> &str {
+        &self.map_name
+    }
+
+    pub fn is_ingame(&self) -> bool {
+        self.ingame
+    }
+
+    pub fn get_match_data(&self) -> &MatchData {
+        &self.match_data
+    }
+
     pub fn get_entities(&self) -> &Vec<EntityData> {
         &self.player_data
     }
@@ -0,0 +1,72 @@
+use memflow::prelude::v1::*;
+
+use super::{DmaCtx, cs2dumper};
+
+// CCvar, the ConVar entries are stored in a flat array
+const CVAR_ENTRIES: u64 = 0x40;
+const CVAR_ENTRY_COUNT: u64 = 0xA0;
+const CVAR_ENTRY_SIZE: u64 = 0x10;
+
+// ConVar
+const CONVAR_NAME: u64 = 0x0;
+const CONVAR_VALUE: u64 = 0x40;
+
+const MAX_NAME_LEN: usize = 64;
+
+impl DmaCtx {
+    /// Looks up the given ConVars by name, returns their addresses in the same order
+    pub fn find_convars(&mut self, names: &[&str]) -> anyhow::Result<Vec<Option<Address>>> {
+        let cvar = self.tier0_module.base + cs2dumper::interfaces::tier0_dll::VEngineCvar007;
+
+        let entries = self.process.read_addr64(cvar + CVAR_ENTRIES)?;
+        let count: u16 = self.process.read(cvar + CVAR_ENTRY_COUNT)?;
+
+        // (convar_ptr, name_ptr, name_buff)
+        let mut data_vec: Vec<(u64, u64, [u8; MAX_NAME_LEN])> = (0..count as u64)
+            .map(|_| (0u64, 0u64, [0u8; MAX_NAME_LEN]))
+            .collect();
+
+        // Get the ConVar address
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().enumerate().for_each(|(idx, (convar_ptr, _, _))| {
+            batcher.read_into(entries + idx as u64 * CVAR_ENTRY_SIZE, convar_ptr);
+        });
+        drop(batcher);
+
+        // Get the name address
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(convar_ptr, name_ptr, _)| {
+            let base: Address = (*convar_ptr).into();
+            batcher.read_into(base + CONVAR_NAME, name_ptr);
+        });
+        drop(batcher);
+
+        // Read the name
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(_, name_ptr, name_buff)| {
+            let base: Address = (*name_ptr).into();
+            batcher.read_into(base, name_buff);
+        });
+        drop(batcher);
+
+        let found = names
+            .iter()
+            .map(|name| {
+                data_vec
+                    .iter()
+                    .find(|(convar_ptr, _, name_buff)| {
+                        let name_len = name_buff.iter().position(|&c| c == 0).unwrap_or(MAX_NAME_LEN);
+                        *convar_ptr != 0 && &name_buff[..name_len] == name.as_bytes()
+                    })
+                    .map(|(convar_ptr, _, _)| Address::from(*convar_ptr))
+            })
+            .collect();
+
+        Ok(found)
+    }
+}
+
+/// Address of the value of a ConVar, ints and bools are stored at the start of the value union
+pub fn convar_value(convar: Address) -> Address {
+    convar + CONVAR_VALUE
+}
\ No newline at end of file
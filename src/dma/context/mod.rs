@@ -1,18 +1,30 @@
 use memflow::prelude::v1::*;
 
 mod connector;
+mod convar;
 
 pub use connector::Connector;
+pub use convar::convar_value;
 use num_traits::FromPrimitive;
 
-use crate::{structs::Vec3, enums::TeamID};
+use crate::{structs::{Vec3, PlayerFlags}, enums::{BombSite, HostageState, TeamID}, comms::PlayerStats};
 
 use super::{cs2dumper, threaddata::CsData};
 
+const FL_ONGROUND: u32 = 1 << 0;
+const FL_DUCKING: u32 = 1 << 1;
+const MOVETYPE_LADDER: u8 = 9;
+
+/// Below this a player counts as standing still
+const MIN_MOVE_SPEED: f32 = 5.0;
+/// Shift walking is capped at 52% of the weapon's max speed, knife walk is 130
+const MAX_WALK_SPEED: f32 = 135.0;
+
 pub struct DmaCtx {
     pub process: IntoProcessInstanceArcBox<'static>,
     pub client_module: ModuleInfo,
     pub engine_module: ModuleInfo,
+    pub tier0_module: ModuleInfo,
 }
 
 impl DmaCtx {
@@ -62,10 +74,13 @@ impl DmaCtx {
 
         let engine_module = process.module_by_name("engine2.dll")?;
 
+        let tier0_module = process.module_by_name("tier0.dll")?;
+
         let mut ctx = Self {
             process,
             client_module,
             engine_module,
+            tier0_module,
         };
 
         if !skip_version {
@@ -77,6 +92,16 @@ impl DmaCtx {
 
     pub fn pawn_from_controller(&mut self, controller: Address, entity_list: Address) -> anyhow::Result<Option<Address>> {
         let uhandle: u32 = self.process.read(controller + cs2dumper::client::CCSPlayerController::m_hPlayerPawn)?;
+        self.entity_from_handle(uhandle, entity_list)
+
+        //super::CPlayerPawn::from_uhandle(ctx, entity_list, uhandle)
+    }
+
+    pub fn entity_from_handle(&mut self, uhandle: u32, entity_list: Address) -> anyhow::Result<Option<Address>> {
+        // Invalid handles have all index bits set
+        if uhandle & 0x7FFF == 0x7FFF {
+            return Ok(None);
+        }
 
         let list_entry = self.process.read_addr64(entity_list + 0x8 * ((uhandle & 0x7FFF) >> 9) + 16)?;
         
@@ -86,8 +111,37 @@ impl DmaCtx {
             let ptr = self.process.read_addr64(list_entry + 120 * (uhandle & 0x1FF))?;
             Ok(Some(ptr))
         }
+    }
 
-        //super::CPlayerPawn::from_uhandle(ctx, entity_list, uhandle)
+    /// Resolves the pawn the given controller is currently spectating through its observer services.
+    /// Returns `None` if the controller isn't observing anyone.
+    pub fn observer_target(&mut self, controller: Address, entity_list: Address) -> anyhow::Result<Option<Address>> {
+        let observer_handle: u32 = self.process.read(controller + cs2dumper::client::CCSPlayerController::m_hObserverPawn)?;
+
+        let Some(observer_pawn) = self.entity_from_handle(observer_handle, entity_list)? else {
+            return Ok(None);
+        };
+
+        let observer_services = self.process.read_addr64(observer_pawn + cs2dumper::client::C_BasePlayerPawn::m_pObserverServices)?;
+        if observer_services.is_null() {
+            return Ok(None);
+        }
+
+        let mut observer_mode = 0u8;
+        let mut target_handle = 0u32;
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(observer_services + cs2dumper::client::CPlayer_ObserverServices::m_iObserverMode, &mut observer_mode);
+            batcher.read_into(observer_services + cs2dumper::client::CPlayer_ObserverServices::m_hObserverTarget, &mut target_handle);
+        }
+
+        // OBS_MODE_NONE
+        if observer_mode == 0 {
+            return Ok(None);
+        }
+
+        let target = self.entity_from_handle(target_handle, entity_list)?;
+        Ok(target.filter(|target| !target.is_null() && *target != observer_pawn))
     }
 
     pub fn batched_player_read(&mut self, controller: Address, pawn: Address) -> anyhow::Result<BatchedPlayerData> {
@@ -100,6 +154,13 @@ impl DmaCtx {
         let mut player_name_ptr = 0u64;
         let mut money = 0i32;
         let mut money_services_ptr = 0u64;
+        let mut vel = Vec3::default();
+        let mut entity_flags = 0u32;
+        let mut move_type = 0u8;
+        let mut is_defusing = 0u8;
+        let mut flash_duration = 0f32;
+        let mut spotted_mask = [0u32; 2];
+        let mut equipment_value = 0u16;
 
         {
             let mut batcher = MemoryViewBatcher::new(&mut self.process);
@@ -112,6 +173,15 @@ impl DmaCtx {
             batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_sSanitizedPlayerName, &mut player_name_ptr);
 
             batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_pInGameMoneyServices, &mut money_services_ptr);
+
+            batcher.read_into(pawn + cs2dumper::client::C_BaseEntity::m_vecAbsVelocity, &mut vel);
+            batcher.read_into(pawn + cs2dumper::client::C_BaseEntity::m_fFlags, &mut entity_flags);
+            batcher.read_into(pawn + cs2dumper::client::C_BaseEntity::m_MoveType, &mut move_type);
+            batcher.read_into(pawn + cs2dumper::client::C_CSPlayerPawn::m_bIsDefusing, &mut is_defusing);
+            batcher.read_into(pawn + cs2dumper::client::C_CSPlayerPawnBase::m_flFlashDuration, &mut flash_duration);
+            batcher.read_into(pawn + cs2dumper::client::C_CSPlayerPawn::m_entitySpottedState
+                + cs2dumper::client::EntitySpottedState_t::m_bSpottedByMask, &mut spotted_mask);
+            batcher.read_into(pawn + cs2dumper::client::C_CSPlayerPawn::m_unCurrentEquipmentValue, &mut equipment_value);
         }
 
         if money_services_ptr != 0 {
@@ -135,16 +205,37 @@ impl DmaCtx {
 
         let team = TeamID::from_i32(team);
 
-        let (has_awp, weapon_id) = {
+        let (has_awp, weapon_id, is_reloading) = if clipping_weapon == 0 {
+            // Dead or spectating, nothing in hand
+            (false, 0, false)
+        } else {
             let clipping_weapon: Address = clipping_weapon.into();
             let items_def_idx_addr = clipping_weapon + cs2dumper::client::C_EconEntity::m_AttributeManager 
                 + cs2dumper::client::C_AttributeContainer::m_Item + cs2dumper::client::C_EconItemView::m_iItemDefinitionIndex;
-    
-            let items_def_idx: i16 = self.process.read(items_def_idx_addr)?;
 
-            (items_def_idx == 9, items_def_idx)
+            let mut items_def_idx = 0i16;
+            let mut in_reload = 0u8;
+            {
+                let mut batcher = self.process.batcher();
+                batcher.read_into(items_def_idx_addr, &mut items_def_idx);
+                batcher.read_into(clipping_weapon + cs2dumper::client::C_CSWeaponBase::m_bInReload, &mut in_reload);
+            }
+
+            (items_def_idx == 9, items_def_idx, in_reload != 0)
         };
 
+        let mut flags = PlayerFlags::default();
+        let speed = vel.length_2d();
+
+        flags.set(PlayerFlags::FLASHED, flash_duration > 0.0);
+        flags.set(PlayerFlags::DEFUSING, is_defusing != 0);
+        flags.set(PlayerFlags::RELOADING, is_reloading);
+        flags.set(PlayerFlags::CROUCHING, entity_flags & FL_DUCKING != 0);
+        flags.set(PlayerFlags::ON_GROUND, entity_flags & FL_ONGROUND != 0);
+        flags.set(PlayerFlags::ON_LADDER, move_type == MOVETYPE_LADDER);
+        flags.set(PlayerFlags::WALKING, speed > MIN_MOVE_SPEED && speed <= MAX_WALK_SPEED);
+        flags.set(PlayerFlags::RUNNING, speed > MAX_WALK_SPEED);
+
         Ok(BatchedPlayerData {
             pos,
             yaw,
@@ -155,9 +246,136 @@ impl DmaCtx {
             player_name,
             weapon_id,
             money,
+            vel,
+            flags,
+            flash_duration,
+            spotted_mask: spotted_mask[0] as u64 | (spotted_mask[1] as u64) << 32,
+            equipment_value: equipment_value as i32,
         })
     }
 
+    pub fn batched_hostage_read(&mut self, hostage: Address, entity_list: Address) -> anyhow::Result<BatchedHostageData> {
+        let mut scene_node = 0u64;
+        let mut is_rescued = 0u8;
+        let mut leader_handle = 0u32;
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(hostage + cs2dumper::client::C_BaseEntity::m_pGameSceneNode, &mut scene_node);
+            batcher.read_into(hostage + cs2dumper::client::C_Hostage::m_isRescued, &mut is_rescued);
+            batcher.read_into(hostage + cs2dumper::client::C_Hostage::m_leader, &mut leader_handle);
+        }
+
+        let scene_node: Address = scene_node.into();
+        let pos: Vec3 = self.process.read(scene_node + cs2dumper::client::CGameSceneNode::m_vecAbsOrigin)?;
+
+        let leader = if is_rescued != 0 {
+            None
+        } else {
+            self.entity_from_handle(leader_handle, entity_list)?
+        };
+
+        let state = {
+            if is_rescued != 0 {
+                HostageState::Rescued
+            } else if leader.is_some() {
+                HostageState::Following
+            } else {
+                HostageState::Idle
+            }
+        };
+
+        Ok(BatchedHostageData {
+            pos,
+            state,
+            leader,
+        })
+    }
+
+    /// Reads the centers of bombsite A and B out of the player resource
+    pub fn get_bombsite_centers(&mut self, player_resource: Address) -> anyhow::Result<Vec<(BombSite, Vec3)>> {
+        let mut center_a = Vec3::default();
+        let mut center_b = Vec3::default();
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(player_resource + cs2dumper::client::C_CSPlayerResource::m_bombsiteCenterA, &mut center_a);
+            batcher.read_into(player_resource + cs2dumper::client::C_CSPlayerResource::m_bombsiteCenterB, &mut center_b);
+        }
+
+        Ok(vec![(BombSite::A, center_a), (BombSite::B, center_b)])
+    }
+
+    /// Reads the hostage rescue zone positions out of the player resource, unused slots are all zero
+    pub fn get_rescue_zones(&mut self, player_resource: Address) -> anyhow::Result<Vec<Vec3>> {
+        let mut rescue_x = [0i32; 4];
+        let mut rescue_y = [0i32; 4];
+        let mut rescue_z = [0i32; 4];
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(player_resource + cs2dumper::client::C_CSPlayerResource::m_hostageRescueX, &mut rescue_x);
+            batcher.read_into(player_resource + cs2dumper::client::C_CSPlayerResource::m_hostageRescueY, &mut rescue_y);
+            batcher.read_into(player_resource + cs2dumper::client::C_CSPlayerResource::m_hostageRescueZ, &mut rescue_z);
+        }
+
+        let zones = (0..4)
+            .filter(|&idx| rescue_x[idx] != 0 || rescue_y[idx] != 0 || rescue_z[idx] != 0)
+            .map(|idx| Vec3 {
+                x: rescue_x[idx] as f32,
+                y: rescue_y[idx] as f32,
+                z: rescue_z[idx] as f32,
+            })
+            .collect();
+
+        Ok(zones)
+    }
+
+    pub fn has_defuser(&mut self, pawn: Address) -> anyhow::Result<bool> {
+        let item_services = self.process.read_addr64(pawn + cs2dumper::client::C_BasePlayerPawn::m_pItemServices)?;
+        let has_defuser: u8 = self.process.read(item_services + cs2dumper::client::CCSPlayer_ItemServices::m_bHasDefuser)?;
+        Ok(has_defuser != 0)
+    }
+
+    pub fn player_stats(&mut self, controller: Address) -> anyhow::Result<PlayerStats> {
+        let mut action_tracking = 0u64;
+        let mut mvps = 0i32;
+        let mut ping = 0u32;
+        let mut rank = 0i32;
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_pActionTrackingServices, &mut action_tracking);
+            batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_iMVPs, &mut mvps);
+            batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_iPing, &mut ping);
+            batcher.read_into(controller + cs2dumper::client::CCSPlayerController::m_iCompetitiveRanking, &mut rank);
+        }
+
+        let mut stats = PlayerStats {
+            mvps,
+            ping,
+            rank,
+            ..Default::default()
+        };
+
+        if action_tracking == 0 {
+            return Ok(stats);
+        }
+
+        let match_stats = Address::from(action_tracking) + cs2dumper::client::CCSPlayerController_ActionTrackingServices::m_matchStats;
+        let mut headshot_kills = 0i32;
+        {
+            let mut batcher = self.process.batcher();
+            batcher.read_into(match_stats + cs2dumper::client::CSPerRoundStats_t::m_iKills, &mut stats.kills);
+            batcher.read_into(match_stats + cs2dumper::client::CSPerRoundStats_t::m_iDeaths, &mut stats.deaths);
+            batcher.read_into(match_stats + cs2dumper::client::CSPerRoundStats_t::m_iAssists, &mut stats.assists);
+            batcher.read_into(match_stats + cs2dumper::client::CSPerRoundStats_t::m_iDamage, &mut stats.damage);
+            batcher.read_into(match_stats + cs2dumper::client::CSMatchStats_t::m_iHeadShotKills, &mut headshot_kills);
+        }
+
+        if stats.kills > 0 {
+            stats.headshot_percentage = headshot_kills as f32 / stats.kills as f32 * 100.0;
+        }
+
+        Ok(stats)
+    }
+
     pub fn get_plantedc4(&mut self) -> anyhow::Result<Address> {
         let ptr = self.process.read_addr64(self.client_module.base + cs2dumper::offsets::client_dll::dwPlantedC4)?;
         let ptr2 = self.process.read_addr64(ptr)?;
@@ -174,6 +392,65 @@ impl DmaCtx {
         Ok(is_controller)
     }
 
+    /// Walks the whole entity list and returns every entity with the given designer name
+    pub fn find_entities_by_designer_name(&mut self, entity_list: Address, highest_index: i32, name: &str) -> Vec<Address> {
+        let name_len = name.len();
+
+        // (entity_ptr, index, entity_identity_ptr, designer_name_ptr, designer_name_buff)
+        let mut data_vec: Vec<(u64, i32, u64, u64, Vec<u8>)> = (0..=highest_index)
+            .map(|idx| (0u64, idx, 0u64, 0u64, vec![0u8; name_len + 1]))
+            .collect();
+
+        // Get the list entry
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(ptr, idx, _, _, _)| {
+            batcher.read_into(entity_list + 8 * (*idx >> 9) + 16, ptr);
+        });
+        drop(batcher);
+
+        // Get the actual entity address
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(ptr, idx, _, _, _)| {
+            let base: Address = (*ptr).into();
+            batcher.read_into(base + 120 * (*idx & 0x1FF), ptr);
+        });
+        drop(batcher);
+
+        // Get the entity identity address
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(ptr, _, ent_ident_ptr, _, _)| {
+            let base: Address = (*ptr).into();
+            batcher.read_into(base + cs2dumper::client::CEntityInstance::m_pEntity, ent_ident_ptr);
+        });
+        drop(batcher);
+
+        // Get the designer name address
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(_, _, ent_ident_ptr, designer_name_ptr, _)| {
+            let base: Address = (*ent_ident_ptr).into();
+            batcher.read_into(base + cs2dumper::client::CEntityIdentity::m_designerName, designer_name_ptr);
+        });
+        drop(batcher);
+
+        // Read the designer name, including the null terminator so prefixes don't match
+        let mut batcher = self.process.batcher();
+        data_vec.iter_mut().for_each(|(_, _, _, designer_name_ptr, designer_name_buff)| {
+            let base: Address = (*designer_name_ptr).into();
+            batcher.read_raw_into(base, designer_name_buff);
+        });
+        drop(batcher);
+
+        data_vec
+            .into_iter()
+            .filter(|(ptr, _, _, _, designer_name_buff)| {
+                *ptr != 0
+                    && &designer_name_buff[..name_len] == name.as_bytes()
+                    && designer_name_buff[name_len] == 0
+            })
+            .map(|(ptr, _, _, _, _)| Address::from(ptr))
+            .collect()
+    }
+
     pub fn get_c4_holder(&mut self, pawns: Vec<Address>, entity_list: Address, csdata: &CsData) -> Option<Address> {
 
         if csdata.bomb_dropped || csdata.bomb_planted {
@@ -284,4 +561,18 @@ pub struct BatchedPlayerData {
     pub player_name: String,
     pub weapon_id: i16,
     pub money: i32,
+    pub vel: Vec3,
+    pub flags: PlayerFlags,
+    pub flash_duration: f32,
+    /// Bit per player slot that currently sees this player
+    pub spotted_mask: u64,
+    pub equipment_value: i32,
+}
+
+#[derive(Debug)]
+pub struct BatchedHostageData {
+    pub pos: Vec3,
+    pub state: HostageState,
+    // Pawn of the player leading the hostage
+    pub leader: Option<Address>,
 }
\ No newline at end of file
@@ -6,7 +6,9 @@
 mod client_mod;
 mod engine2_mod;
 mod offsets_mod;
+mod interfaces_mod;
 
 pub use client_mod::cs2_dumper::schemas::client_dll as client;
 pub use engine2_mod::cs2_dumper::schemas::engine2_dll as engine;
-pub use offsets_mod::cs2_dumper::offsets as offsets;
\ No newline at end of file
+pub use offsets_mod::cs2_dumper::offsets as offsets;
+pub use interfaces_mod::cs2_dumper::interfaces as interfaces;
\ No newline at end of file
@@ -1,12 +1,22 @@
-use std::{thread, time::{Duration, Instant}};
+use std::{collections::{HashMap, HashSet}, sync::Arc, thread, time::{Duration, Instant}};
 
 use memflow::{mem::MemoryView, os::Process, types::Address};
 
-use crate::{enums::PlayerType, comms::{EntityData, PlayerData, RadarData, ArcRwlockRadarData, BombData}};
+use num_traits::FromPrimitive;
+
+use crate::{structs::{Vec3, PlayerFlags}, enums::{BombSite, MarkerKind, Perspective, PlayerType, TeamID}, comms::{EntityData, PlayerData, RadarData, ArcRwlockRadarData, BombData, HostageData}};
 
 use crate::money_reveal::MoneyReveal;
+use crate::events::{self, EventSender, EventState};
+use crate::smoothing::Smoother;
+use crate::history::History;
+use crate::economy::{self, PlayerEconomy};
+use crate::maps::{self, ArcMapRegistry, MapInfo};
+use crate::summary::{self, SummaryPlayer};
+use crate::nav::{self, ArcNavStore, NavMesh};
+use crate::estimation::HeatCache;
 
-use self::{context::DmaCtx, threaddata::CsData};
+use self::{context::DmaCtx, threaddata::{CsData, PlayerStatus}};
 
 pub mod context;
 pub mod threaddata;
@@ -14,7 +24,7 @@ mod cs2dumper;
 
 pub use context::Connector;
 
-pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_device: String, skip_version: bool) -> anyhow::Result<()> {
+pub async fn run(radar_data: ArcRwlockRadarData, events: EventSender, mut smoother: Option<Smoother>, trail_seconds: f32, fog_of_war: bool, maps: ArcMapRegistry, radar_pos: bool, tactical_summary: bool, heat_navs: Option<ArcNavStore>, connector: Connector, pcileech_device: String, skip_version: bool) -> anyhow::Result<()> {
     let mut ctx = DmaCtx::setup(connector, pcileech_device, skip_version)?;
     let mut data = CsData {
         recheck_bomb_holder: true,
@@ -22,6 +32,8 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
         ..Default::default()
     };
 
+    let mut history = History::new(trail_seconds);
+
     let mut money_reveal = MoneyReveal::new();
     if let Err(e) = money_reveal.init(&mut ctx.process, &ctx.client_module) {
         log::warn!("Failed to initialize money reveal: {}", e);
@@ -34,6 +46,7 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
     let mut last_round_start_count = 0u8;
     let mut last_tick_count = 0;
     let mut last_big_read = Instant::now();
+    let mut last_stats_read = Instant::now();
 
     // For frequency info
     let mut start_stamp = Instant::now();
@@ -43,7 +56,23 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
     data.update_pointers(&mut ctx);
     data.update_common(&mut ctx);
     data.update_players(&mut ctx);
+    data.update_teams(&mut ctx);
+    data.update_convars(&mut ctx);
+    data.update_hostages(&mut ctx);
+    data.update_bombsites(&mut ctx);
     data.update_bomb(&mut ctx);
+    data.update_stats(&mut ctx);
+
+    // Calibration of the current map, for layers
+    let mut map_info: Option<MapInfo> = None;
+    let mut map_info_name = String::new();
+
+    // Nav mesh of the current map, only loaded if the heat layer is enabled
+    let mut nav_mesh: Option<Arc<NavMesh>> = None;
+    let mut heat_cache = HeatCache::default();
+
+    // For event generation
+    let mut last_events = EventState::new(&data);
 
     loop {
         if ctx.process.state().is_dead() {
@@ -53,7 +82,32 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
         if last_big_read.elapsed().as_millis() > 10000 {
             data.update_pointers(&mut ctx);
             data.update_players(&mut ctx);
+            data.update_teams(&mut ctx);
+            data.update_convars(&mut ctx);
+            data.update_hostages(&mut ctx);
+            data.update_bombsites(&mut ctx);
             last_big_read = Instant::now();
+
+            // Picks up edits to the map json
+            map_info_name.clear();
+        }
+
+        if map_info_name != data.map {
+            map_info = maps.read().await
+                .get(&data.map)
+                .and_then(|entry| entry.info.clone());
+
+            if let Some(navs) = &heat_navs {
+                nav_mesh = nav::get(navs, &data.map).await;
+                heat_cache = HeatCache::default();
+            }
+
+            map_info_name = data.map.clone();
+        }
+
+        if last_stats_read.elapsed().as_millis() > 1000 {
+            data.update_stats(&mut ctx);
+            last_stats_read = Instant::now();
         }
 
         data.update_common(&mut ctx);
@@ -67,6 +121,8 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
                     log::warn!("Failed to toggle money reveal: {}", e);
                 }
             }
+
+            data.perspective = radar.perspective;
         }
 
         // Bomb update
@@ -87,6 +143,8 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
         if last_round_start_count != data.round_start_count {
             log::debug!("Bomb holder recheck due to round start");
             data.recheck_bomb_holder = true;
+            data.update_hostages(&mut ctx);
+            history.clear();
         }
 
         last_freeze_period = data.freeze_period;
@@ -105,6 +163,10 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
 
             data.bomb_holder = ctx.get_c4_holder(pawns, data.entity_list.into(), &data);
 
+            if data.bomb_holder != prev_holder {
+                data.update_c4_weapon(&mut ctx);
+            }
+
             if data.bomb_holder.is_some() && prev_holder.is_none() {
                 log::debug!("Bomb picked up by player");
                 data.bomb_dropped = false;
@@ -113,48 +175,24 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
             data.recheck_bomb_holder = false;
         }
 
-        let bomb_defuse_timeleft: f32 = {
-            if data.bomb_planted && !data.bomb_exploded && !data.bomb_defused {
-                if let Some(bomb_stamp) = data.bomb_planted_stamp {
-                    data.bomb_plant_timer - bomb_stamp.elapsed().as_secs_f32()
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            }
-        };
-
-        let bomb_can_defuse: bool = {
-            if data.bomb_planted && !data.bomb_exploded && !data.bomb_defused {
-                if let (Some(bomb_stamp), Some(defuse_stamp)) = (data.bomb_planted_stamp, data.bomb_defuse_stamp) {
-                    let time_left = data.bomb_plant_timer - bomb_stamp.elapsed().as_secs_f32();
-                    let defuse_left = data.bomb_defuse_length - defuse_stamp.elapsed().as_secs_f32();
-                    time_left - defuse_left > 0.0
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        };
-
-        let bomb_defuse_end: f32 = {
-            if bomb_can_defuse {
-                if let (Some(bomb_stamp), Some(defuse_stamp)) = (data.bomb_planted_stamp, data.bomb_defuse_stamp) {
-                    let defuse_left = data.bomb_defuse_length - defuse_stamp.elapsed().as_secs_f32();
-                    (data.bomb_plant_timer - bomb_stamp.elapsed().as_secs_f32()) - defuse_left
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            }
-        };
+        let (bomb_defuse_timeleft, bomb_can_defuse, bomb_defuse_end) = data.bomb_timers();
 
         last_bomb_dropped = data.bomb_dropped;
         last_bomb_planted = data.bomb_planted;
 
+        // Events, nothing can change without a new tick or map
+        if last_events.is_outdated(&data) {
+            let current = EventState::new(&data);
+
+            for event in events::diff(&last_events, &current) {
+                log::debug!("Game event: {:?}", event.kind);
+                // Only fails if nobody is subscribed
+                let _ = events.send(event);
+            }
+
+            last_events = current;
+        }
+
         // Poll entity data
         let ingame = !data.map.is_empty() && data.map != "<empty>";
         let update_data = data.tick_count != last_tick_count;
@@ -165,17 +203,10 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
             }
 
             let mut entity_data = Vec::new();
+            let plant_progress = data.plant_progress();
 
-            // Bomb
-            if data.bomb_dropped || data.bomb_planted {
-                if let Ok(node) = ctx.process.read_addr64(
-                    data.bomb + cs2dumper::client::C_BaseEntity::m_pGameSceneNode as u64
-                ) {
-                    if let Ok(pos) = ctx.process.read(node + cs2dumper::client::CGameSceneNode::m_vecAbsOrigin) {
-                        entity_data.push(EntityData::Bomb(BombData::new(pos, data.bomb_planted)));
-                    }
-                }
-            }
+            // Controllers that are in the frame as players, everyone else becomes a marker
+            let mut seen_players = HashSet::new();
 
             // Local player
             let local_data = match ctx.batched_player_read(
@@ -188,82 +219,390 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
                 }
             };
 
+            // While dead or spectating, follow whoever we are observing
+            data.observer_target = if local_data.health > 0 {
+                None
+            } else {
+                match ctx.observer_target(data.local.into(), data.entity_list.into()) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        log::debug!("Failed to read observer target: {}", e);
+                        None
+                    }
+                }
+            };
+
+            let mut player_status: HashMap<Address, PlayerStatus> = HashMap::new();
+            let local_lookup = (local_data.player_name.clone(), local_data.pos);
+
+            player_status.insert(data.local.into(), PlayerStatus {
+                name: local_data.player_name.clone(),
+                team: local_data.team,
+                alive: local_data.health > 0,
+            });
+
             if local_data.health > 0 {
                 let has_bomb = match data.bomb_holder {
                     Some(bh) => data.local_pawn == bh.to_umem(),
                     None => false,
                 };
 
-                entity_data.push(
-                    EntityData::Player(
-                        PlayerData::new(
-                            local_data.pos,
-                            local_data.yaw,
-                            PlayerType::Local,
-                            has_bomb,
-                            local_data.has_awp,
-                            local_data.is_scoped,
-                            local_data.player_name,
-                            local_data.weapon_id,
-                            local_data.money,
-                            local_data.health
-                        )
-                    )
+                let mut player = PlayerData::new(
+                    local_data.pos,
+                    local_data.yaw,
+                    PlayerType::Local,
+                    local_data.team,
+                    has_bomb,
+                    local_data.has_awp,
+                    local_data.is_scoped,
+                    local_data.player_name.clone(),
+                    local_data.weapon_id,
+                    local_data.money,
+                    local_data.health,
+                    false
+                );
+
+                player.flags = local_data.flags;
+                player.flags.set(PlayerFlags::PLANTING, has_bomb && plant_progress.is_some());
+                player.flash_duration = local_data.flash_duration;
+                player.vel = local_data.vel;
+                player.stats = data.player_stats.get(&Address::from(data.local)).copied();
+
+                history.record(
+                    data.local.into(),
+                    &local_data.player_name,
+                    PlayerType::Local,
+                    local_data.team,
+                    local_data.pos,
+                    local_data.yaw,
+                    data.cur_time
                 );
+                player.trail = history.trail(data.local.into());
+                seen_players.insert(Address::from(data.local));
+
+                if let Some(smoother) = &mut smoother {
+                    let (filtered, predicted) = smoother.update(data.local_pawn.into(), local_data.pos, local_data.vel, data.cur_time);
+                    player.filtered_pos = Some(filtered);
+                    player.predicted_pos = Some(predicted);
+                }
+
+                entity_data.push(EntityData::Player(player));
+            } else {
+                history.mark_dead(data.local.into());
             }
 
             // Other players
+            let mut player_reads = Vec::with_capacity(data.players.len());
             for (controller, pawn) in &data.players {
                 match ctx.batched_player_read(*controller, *pawn) {
                     Ok(player_data) => {
-                        if player_data.health < 1 {
-                            continue;
+                        player_status.insert(*controller, PlayerStatus {
+                            name: player_data.player_name.clone(),
+                            team: player_data.team,
+                            alive: player_data.health > 0,
+                        });
+
+                        player_reads.push((*controller, *pawn, player_data));
+                    },
+                    Err(e) => {
+                        log::warn!("Failed to read player data: {}", e);
+                        continue;
+                    }
+                }
+            }
+
+            let reference_team = match data.perspective {
+                Perspective::CT => Some(TeamID::CT),
+                Perspective::T => Some(TeamID::T),
+                // Spectators don't have a side, so they look at the game from the side of whoever they observe
+                Perspective::Local => {
+                    if local_data.team == Some(TeamID::Spectator) {
+                        data.observer_target
+                            .and_then(|target| player_reads.iter().find(|(_, pawn, _)| *pawn == target))
+                            .and_then(|(_, _, player_data)| player_data.team)
+                    } else {
+                        local_data.team
+                    }
+                }
+            };
+
+            // Used to resolve hostage carriers, planters and defusers
+            let mut pawn_lookup: HashMap<Address, (String, Vec3)> = player_reads
+                .iter()
+                .map(|(_, pawn, player_data)| (*pawn, (player_data.player_name.clone(), player_data.pos)))
+                .collect();
+
+            pawn_lookup.insert(data.local_pawn.into(), local_lookup);
+
+            // Player slots whose vision counts in fog of war mode
+            let viewer_mask = if fog_of_war {
+                let slot_bit = |controller: Address| {
+                    data.player_slots
+                        .get(&controller)
+                        .map(|slot| 1u64 << slot)
+                        .unwrap_or(0)
+                };
+
+                if data.teammates_are_enemies {
+                    // Nobody shares vision, only whoever we are looking through counts
+                    if local_data.health > 0 {
+                        slot_bit(data.local.into())
+                    } else {
+                        data.observer_target
+                            .and_then(|target| player_reads.iter().find(|(_, pawn, _)| *pawn == target))
+                            .map(|(controller, _, _)| slot_bit(*controller))
+                            .unwrap_or(0)
+                    }
+                } else {
+                    let mut mask = 0;
+
+                    if local_data.health > 0 && reference_team.is_some() && local_data.team == reference_team {
+                        mask |= slot_bit(data.local.into());
+                    }
+
+                    for (controller, _, player_data) in &player_reads {
+                        if player_data.health > 0 && reference_team.is_some() && player_data.team == reference_team {
+                            mask |= slot_bit(*controller);
                         }
+                    }
 
-                        let has_bomb = match data.bomb_holder {
-                            Some(bh) => *pawn == bh,
-                            None => false,
-                        };
-
-                        let player_type = {
-                            if local_data.team != player_data.team {
-                                PlayerType::Enemy
-                            } else if local_data.team == player_data.team {
-                                PlayerType::Team
-                            } else {
-                                PlayerType::Unknown
-                            }
-                        };
+                    mask
+                }
+            } else {
+                0
+            };
+
+            // Players are still buying during freeze time, after that the numbers would only count what's left
+            if data.freeze_period {
+                let mut players: Vec<PlayerEconomy> = player_reads
+                    .iter()
+                    .map(|(_, _, player_data)| PlayerEconomy {
+                        team: player_data.team,
+                        money: player_data.money,
+                        equipment_value: player_data.equipment_value,
+                    })
+                    .collect();
+
+                players.push(PlayerEconomy {
+                    team: local_data.team,
+                    money: local_data.money,
+                    equipment_value: local_data.equipment_value,
+                });
+
+                data.economy = economy::compute(&players, data.ct_loss_streak, data.t_loss_streak);
+            }
+
+            let mut hidden_pawns = HashSet::new();
+
+            for (controller, pawn, player_data) in player_reads {
+                if player_data.health < 1 {
+                    history.mark_dead(controller);
+                    continue;
+                }
+
+                let has_bomb = match data.bomb_holder {
+                    Some(bh) => pawn == bh,
+                    None => false,
+                };
+
+                let player_type = {
+                    if player_data.team == Some(TeamID::Spectator) {
+                        PlayerType::Spectator
+                    } else if data.teammates_are_enemies {
+                        PlayerType::Enemy
+                    } else if reference_team.is_none() || player_data.team.is_none() {
+                        PlayerType::Unknown
+                    } else if reference_team != player_data.team {
+                        PlayerType::Enemy
+                    } else {
+                        PlayerType::Team
+                    }
+                };
+
+                // Enemies nobody on our side can see only show up as last known markers
+                if fog_of_war && player_type == PlayerType::Enemy && player_data.spotted_mask & viewer_mask == 0 {
+                    hidden_pawns.insert(pawn);
+                    continue;
+                }
+
+                let is_observed = data.observer_target == Some(pawn);
+
+                let mut player = PlayerData::new(
+                    player_data.pos,
+                    player_data.yaw,
+                    player_type,
+                    player_data.team,
+                    has_bomb,
+                    player_data.has_awp,
+                    player_data.is_scoped,
+                    player_data.player_name.clone(),
+                    player_data.weapon_id,
+                    player_data.money,
+                    player_data.health,
+                    is_observed
+                );
+
+                player.flags = player_data.flags;
+                player.flags.set(PlayerFlags::PLANTING, has_bomb && plant_progress.is_some());
+                player.flash_duration = player_data.flash_duration;
+                player.vel = player_data.vel;
+                player.stats = data.player_stats.get(&controller).copied();
+
+                history.record(
+                    controller,
+                    &player_data.player_name,
+                    player_type,
+                    player_data.team,
+                    player_data.pos,
+                    player_data.yaw,
+                    data.cur_time
+                );
+                player.trail = history.trail(controller);
+                seen_players.insert(controller);
+
+                if let Some(smoother) = &mut smoother {
+                    let (filtered, predicted) = smoother.update(pawn, player_data.pos, player_data.vel, data.cur_time);
+                    player.filtered_pos = Some(filtered);
+                    player.predicted_pos = Some(predicted);
+                }
+
+                entity_data.push(EntityData::Player(player));
+            }
+
+            let markers = history.markers(&seen_players, data.cur_time);
+
+            let mut heat = if heat_navs.is_some() {
+                let lost = markers
+                    .iter()
+                    .filter(|marker| marker.kind() == MarkerKind::LastKnown && marker.player_type() == PlayerType::Enemy)
+                    .map(|marker| (marker.player_name(), marker.pos(), marker.age()));
+
+                heat_cache.update(lost, nav_mesh.as_deref())
+            } else {
+                Vec::new()
+            };
+
+            entity_data.extend(markers.into_iter().map(EntityData::Marker));
+
+            // Hostages
+            for hostage in &data.hostages {
+                match ctx.batched_hostage_read(*hostage, data.entity_list.into()) {
+                    Ok(hostage_data) => {
+                        let carrier = hostage_data.leader
+                            .filter(|leader| !hidden_pawns.contains(leader))
+                            .and_then(|leader| pawn_lookup.get(&leader))
+                            .map(|(name, _)| name.clone());
 
                         entity_data.push(
-                            EntityData::Player(
-                                PlayerData::new(
-                                    player_data.pos,
-                                    player_data.yaw,
-                                    player_type,
-                                    has_bomb,
-                                    player_data.has_awp,
-                                    player_data.is_scoped,
-                                    player_data.player_name,
-                                    player_data.weapon_id,
-                                    player_data.money,
-                                    player_data.health
-                                )
+                            EntityData::Hostage(
+                                HostageData::new(hostage_data.pos, hostage_data.state, carrier)
                             )
                         );
                     },
                     Err(e) => {
-                        log::warn!("Failed to read player data: {}", e);
-                        continue;
+                        log::debug!("Failed to read hostage data: {}", e);
                     }
                 }
             }
 
+            // Bomb
+            if data.bomb_dropped || data.bomb_planted {
+                if let Ok(node) = ctx.process.read_addr64(
+                    data.bomb + cs2dumper::client::C_BaseEntity::m_pGameSceneNode as u64
+                ) {
+                    if let Ok(pos) = ctx.process.read(node + cs2dumper::client::CGameSceneNode::m_vecAbsOrigin) {
+                        let mut bomb_data = BombData::new(pos, data.bomb_planted);
+
+                        if data.bomb_planted {
+                            bomb_data.site = BombSite::from_i32(data.bomb_site);
+
+                            if data.bomb_being_defused {
+                                // Naming an unspotted defuser would give away where they are
+                                let defuser = ctx.entity_from_handle(data.bomb_defuser_handle, data.entity_list.into())
+                                    .ok()
+                                    .flatten()
+                                    .filter(|defuser| !hidden_pawns.contains(defuser));
+
+                                if let Some(defuser) = defuser {
+                                    bomb_data.defuser = pawn_lookup.get(&defuser).map(|(name, _)| name.clone());
+                                    bomb_data.defuser_has_kit = ctx.has_defuser(defuser).unwrap_or(false);
+                                }
+                            }
+                        }
+
+                        entity_data.push(EntityData::Bomb(bomb_data));
+                    }
+                }
+            } else if let (Some(holder), Some(progress)) = (data.bomb_holder, plant_progress) {
+                // Bomb is being planted, show it at the planter
+                if let Some((name, pos)) = pawn_lookup.get(&holder).filter(|_| !hidden_pawns.contains(&holder)) {
+                    let mut bomb_data = BombData::new(*pos, false);
+                    bomb_data.planter = Some(name.clone());
+                    bomb_data.plant_progress = Some(progress);
+
+                    entity_data.push(EntityData::Bomb(bomb_data));
+                }
+            }
+
+            if let Some(info) = &map_info {
+                for entity in &mut entity_data {
+                    let pos = entity.pos();
+
+                    if !info.layers.is_empty() {
+                        entity.set_layer(info.layer_at(pos.z).map(str::to_string));
+                    }
+
+                    if radar_pos {
+                        entity.set_radar_pos(Some(maps::world_to_radar(info, &pos)));
+                    }
+
+                    if let EntityData::Player(player) = entity {
+                        player.zone = info.zone_at(&pos).map(str::to_string);
+                    }
+                }
+
+                if radar_pos {
+                    for point in &mut heat {
+                        point.radar_pos = Some(maps::world_to_radar(info, &point.pos));
+                    }
+                }
+            }
+
+            let summary = tactical_summary.then(|| {
+                let players: Vec<SummaryPlayer> = entity_data
+                    .iter()
+                    .filter_map(|entity| match entity {
+                        EntityData::Player(player) => Some(player),
+                        _ => None,
+                    })
+                    .filter(|player| matches!(player.player_type(), PlayerType::Local | PlayerType::Team | PlayerType::Enemy))
+                    .map(|player| SummaryPlayer {
+                        name: player.player_name().to_string(),
+                        pos: player.pos(),
+                        friendly: player.player_type() != PlayerType::Enemy,
+                        zone: player.zone.clone(),
+                        has_bomb: player.has_bomb(),
+                    })
+                    .collect();
+
+                summary::compute(&players, &data.bombsite_centers)
+            });
+
+            data.player_status = player_status;
+
+            if let Some(smoother) = &mut smoother {
+                let pawns: Vec<Address> = pawn_lookup.keys().copied().collect();
+                smoother.retain(&pawns);
+            }
+
             let mut radar = radar_data.write().await;
             *radar = RadarData::new(
                 true,
                 data.map.clone(),
+                data.match_data(),
+                data.game_mode_data(),
                 entity_data,
+                data.rescue_zones.clone(),
                 freq,
                 data.bomb_planted,
                 bomb_can_defuse,
@@ -275,10 +614,18 @@ pub async fn run(radar_data: ArcRwlockRadarData, connector: Connector, pcileech_
             );
 
             radar.money_reveal_enabled = data.money_reveal_enabled;
+            radar.perspective = data.perspective;
+            radar.scoreboard = data.scoreboard();
+            radar.economy = data.economy;
+            radar.summary = summary;
+            radar.heat = heat;
+            radar.tick = data.tick_count;
+            radar.cur_time = data.cur_time;
         } else {
             let mut radar = radar_data.write().await;
             *radar = RadarData::empty(freq);
             radar.money_reveal_enabled = data.money_reveal_enabled;
+            radar.perspective = data.perspective;
         }
 
         last_tick_count = data.tick_count;
@@ -1,16 +1,46 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 use memflow::{mem::MemoryView, types::Address};
-use tokio::time::Instant;
+use num_traits::FromPrimitive;
+
+use crate::{structs::Vec3, comms::{GameModeData, HostageObjective, MatchData, PlayerStats, ScoreboardEntry}, enums::{BombSite, GameMode, MatchPhase, Perspective, RoundPhase, TeamID}};
+
+use crate::economy::EconomyData;
+
+use super::{context::{convar_value, DmaCtx}, cs2dumper};
 
-use super::{context::DmaCtx, cs2dumper};
+// Seconds it takes to plant the bomb
+const C4_ARM_TIME: f32 = 3.0;
+
+#[derive(Clone, Debug, Default)]
+pub struct PlayerStatus {
+    pub name: String,
+    pub team: Option<TeamID>,
+    pub alive: bool,
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct CsData {
     // Entities
     pub players: Vec<(Address, Address)>,
+    // Controller to player slot, the bit used in spotted masks. Includes the local player
+    pub player_slots: HashMap<Address, u32>,
+    // Keyed by controller, updated on every entity read
+    pub player_status: HashMap<Address, PlayerStatus>,
+    // Keyed by controller, refreshed every second
+    pub player_stats: HashMap<Address, PlayerStats>,
     pub bomb: Address,
     pub bomb_holder: Option<Address>,
     pub recheck_bomb_holder: bool,
+    // The carried weapon_c4, only valid while someone holds the bomb
+    pub c4_weapon: Address,
+    // (team entity, team number)
+    pub teams: Vec<(Address, i32)>,
+    pub player_resource: Address,
+    pub hostages: Vec<Address>,
+    pub rescue_zones: Vec<Vec3>,
+    pub bombsite_centers: Vec<(BombSite, Vec3)>,
 
     // Pointers
     pub globals: u64,
@@ -21,28 +51,139 @@ pub struct CsData {
     // Common
     pub local: u64,
     pub local_pawn: u64,
+    // Pawn the local player is spectating while dead or in the spectator team
+    pub observer_target: Option<Address>,
     // pub is_dead: bool,   // TODO: Why is this here?
     pub tick_count: i32,
+    pub cur_time: f32,
     pub freeze_period: bool,
     pub round_start_count: u8,
     pub highest_index: i32,
     pub map: String,
 
+    // Match
+    pub warmup_period: bool,
+    pub game_phase: i32,
+    pub total_rounds_played: i32,
+    pub overtime_playing: i32,
+    pub round_win_status: i32,
+    pub round_time: i32,
+    pub round_start_time: f32,
+    pub ct_score: i32,
+    pub t_score: i32,
+    pub ct_loss_streak: i32,
+    pub t_loss_streak: i32,
+    // Recomputed during freeze time, kept for the rest of the round
+    pub economy: EconomyData,
+
+    // Game mode
+    pub game_type_convar: Option<Address>,
+    pub game_mode_convar: Option<Address>,
+    pub teammates_are_enemies_convar: Option<Address>,
+    pub game_type: i32,
+    pub game_mode: i32,
+    pub teammates_are_enemies: bool,
+    pub map_has_bomb_target: bool,
+    pub map_has_rescue_zone: bool,
+    pub hostages_remaining: i32,
+    pub any_hostage_reached: bool,
+
     // Bomb
     pub bomb_dropped: bool,
     pub bomb_planted: bool,
-    pub bomb_planted_stamp: Option<Instant>,
-    pub bomb_plant_timer: f32,
+    // Game time the bomb explodes at
+    pub bomb_c4_blow: f32,
     pub bomb_being_defused: bool,
-    pub bomb_defuse_stamp: Option<Instant>,
+    // Game time the current defuse finishes at
+    pub bomb_defuse_countdown: f32,
     pub bomb_defuse_length: f32,
     pub bomb_exploded: bool,
     pub bomb_defused: bool,
+    pub bomb_site: i32,
+    pub bomb_defuser_handle: u32,
+    pub c4_started_arming: bool,
+    // Game time the plant finishes at
+    pub c4_armed_time: f32,
     pub money_reveal_enabled: bool,
+    pub perspective: Perspective,
 }
 
 
 impl CsData {
+    /// Bomb timers derived from the game clock: (time left, can defuse, time left once the defuse finishes)
+    pub fn bomb_timers(&self) -> (f32, bool, f32) {
+        if !self.bomb_planted || self.bomb_exploded || self.bomb_defused {
+            return (0.0, false, 0.0);
+        }
+
+        let timeleft = (self.bomb_c4_blow - self.cur_time).max(0.0);
+
+        let can_defuse = self.bomb_being_defused && self.bomb_defuse_countdown < self.bomb_c4_blow;
+
+        let defuse_end = if can_defuse {
+            self.bomb_c4_blow - self.bomb_defuse_countdown
+        } else {
+            0.0
+        };
+
+        (timeleft, can_defuse, defuse_end)
+    }
+
+    pub fn game_mode_data(&self) -> GameModeData {
+        let mode = GameMode::from_convars(self.game_type, self.game_mode);
+
+        let hostages = if self.map_has_rescue_zone {
+            Some(HostageObjective {
+                remaining: self.hostages_remaining,
+                any_reached: self.any_hostage_reached,
+            })
+        } else {
+            None
+        };
+
+        GameModeData {
+            game_type: self.game_type,
+            game_mode: self.game_mode,
+            mode,
+            ffa: self.teammates_are_enemies,
+            bomb_targets: self.map_has_bomb_target,
+            hostages,
+        }
+    }
+
+    pub fn match_data(&self) -> MatchData {
+        let match_phase = MatchPhase::from_i32(self.game_phase).unwrap_or_default();
+
+        let round_phase = {
+            if self.warmup_period {
+                RoundPhase::Warmup
+            } else if self.freeze_period {
+                RoundPhase::FreezeTime
+            } else if self.round_win_status != 0 {
+                RoundPhase::Over
+            } else {
+                RoundPhase::Live
+            }
+        };
+
+        let round_time_left = match round_phase {
+            RoundPhase::FreezeTime => self.round_start_time - self.cur_time,
+            RoundPhase::Live => self.round_start_time + self.round_time as f32 - self.cur_time,
+            _ => 0.0,
+        };
+
+        MatchData {
+            ct_score: self.ct_score,
+            t_score: self.t_score,
+            round: self.total_rounds_played + 1,
+            round_phase,
+            round_time_left: round_time_left.max(0.0),
+            match_phase,
+            is_halftime: match_phase == MatchPhase::Halftime,
+            overtime: self.overtime_playing,
+        }
+    }
+
     pub fn update_bomb(&mut self, ctx: &mut DmaCtx) {
         if self.bomb_dropped {
             // If the bomb is dropped, do a reverse entity list loop with early exit when we found the bomb.
@@ -137,14 +278,23 @@ impl CsData {
         }
 
         let mut new_players: Vec<u64> = Vec::new();
+        let mut player_slots = HashMap::new();
         player_ptrs
             .into_iter()
-            .for_each(|ptr| {
+            .enumerate()
+            .for_each(|(idx, ptr)| {
                 if ctx.is_cs_player_controller(ptr.into()).unwrap_or(false) {
+                    // Entity 0 is the world, controllers start at 1
+                    if idx > 0 {
+                        player_slots.insert(Address::from(ptr), idx as u32 - 1);
+                    }
+
                     new_players.push(ptr)
                 }
             });
 
+        self.player_slots = player_slots;
+
         let new_players: Vec<(Address, Address)> = new_players
             .into_iter()
             .map(Address::from)
@@ -161,6 +311,57 @@ impl CsData {
         self.players = new_players;
     }
 
+    pub fn update_stats(&mut self, ctx: &mut DmaCtx) {
+        let mut controllers: Vec<Address> = self.players
+            .iter()
+            .map(|(controller, _)| *controller)
+            .collect();
+
+        controllers.push(self.local.into());
+
+        let mut player_stats = HashMap::new();
+        for controller in controllers {
+            match ctx.player_stats(controller) {
+                Ok(stats) => {
+                    player_stats.insert(controller, stats);
+                },
+                Err(e) => {
+                    log::debug!("Failed to read player stats: {}", e);
+                }
+            }
+        }
+
+        self.player_stats = player_stats;
+    }
+
+    /// Everyone with stats, sorted by team and kills
+    pub fn scoreboard(&self) -> Vec<ScoreboardEntry> {
+        let mut scoreboard: Vec<ScoreboardEntry> = self.player_stats
+            .iter()
+            .filter_map(|(controller, stats)| {
+                let status = self.player_status.get(controller)?;
+
+                Some(ScoreboardEntry {
+                    player_name: status.name.clone(),
+                    team: status.team,
+                    alive: status.alive,
+                    stats: *stats,
+                })
+            })
+            .collect();
+
+        scoreboard.sort_by(|a, b| {
+            let team_a = a.team.map(|team| team as i32).unwrap_or(0);
+            let team_b = b.team.map(|team| team as i32).unwrap_or(0);
+
+            team_b.cmp(&team_a)
+                .then(b.stats.kills.cmp(&a.stats.kills))
+                .then(a.stats.deaths.cmp(&b.stats.deaths))
+        });
+
+        scoreboard
+    }
+
     pub fn update_common(&mut self, ctx: &mut DmaCtx) {
         let mut bomb_dropped = 0u8;
         let mut bomb_planted = 0u8;
@@ -169,8 +370,16 @@ impl CsData {
         let mut bomb_exploded = 0u8;
         let mut bomb_defused = 0u8;
         let mut freeze_period = 0u8;
+        let mut warmup_period = 0u8;
+        let mut teammates_are_enemies = 0u8;
+        let mut map_has_bomb_target = 0u8;
+        let mut map_has_rescue_zone = 0u8;
+        let mut any_hostage_reached = 0u8;
+        let mut c4_started_arming = 0u8;
+        let mut team_scores = vec![0i32; self.teams.len()];
         {
             // Globals
+            let cur_time_addr = (self.globals + 0x2C).into();
             let tick_count_addr = (self.globals + 0x40).into();
             let map_addr = (self.globals + 384).into();
 
@@ -179,6 +388,19 @@ impl CsData {
             let bomb_planted_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bBombPlanted as u64).into();
             let total_rounds_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bFreezePeriod as u64).into();
             let round_start_count_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_nRoundStartCount as u64).into();
+            let warmup_period_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bWarmupPeriod as u64).into();
+            let game_phase_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_gamePhase as u64).into();
+            let total_rounds_played_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_totalRoundsPlayed as u64).into();
+            let overtime_playing_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_nOvertimePlaying as u64).into();
+            let round_win_status_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_iRoundWinStatus as u64).into();
+            let round_time_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_iRoundTime as u64).into();
+            let round_start_time_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_fRoundStartTime as u64).into();
+            let map_has_bomb_target_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bMapHasBombTarget as u64).into();
+            let map_has_rescue_zone_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bMapHasRescueZone as u64).into();
+            let hostages_remaining_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_iHostagesRemaining as u64).into();
+            let any_hostage_reached_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_bAnyHostageReached as u64).into();
+            let ct_loss_streak_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_iNumConsecutiveCTLoses as u64).into();
+            let t_loss_streak_addr = (self.gamerules + cs2dumper::client::C_CSGameRules::m_iNumConsecutiveTerroristLoses as u64).into();
 
             // Game Entity System
             let highest_index_addr = (self.game_ent_sys + cs2dumper::offsets::client_dll::dwGameEntitySystem_highestEntityIndex as u64).into();
@@ -194,54 +416,77 @@ impl CsData {
             );
 
             batcher.read_into(tick_count_addr, &mut self.tick_count);
+            batcher.read_into(cur_time_addr, &mut self.cur_time);
             batcher.read_into(bomb_dropped_addr, &mut bomb_dropped);
             batcher.read_into(bomb_planted_addr, &mut bomb_planted);
             batcher.read_into(total_rounds_addr, &mut freeze_period);
             batcher.read_into(round_start_count_addr, &mut self.round_start_count);
             batcher.read_into(highest_index_addr, &mut self.highest_index);
             batcher.read_into(map_addr, &mut map_ptr);
-        }
 
-        {
-            let mut batcher = ctx.process.batcher();
-            if self.bomb_planted {
-
-                batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_flTimerLength , &mut self.bomb_plant_timer);
-                batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bBombDefused, &mut bomb_defused);
-                batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_flDefuseLength, &mut self.bomb_defuse_length);
-                batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bHasExploded, &mut bomb_exploded);
-                batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bBeingDefused, &mut bomb_being_defused);
-
-                drop(batcher);
+            batcher.read_into(warmup_period_addr, &mut warmup_period);
+            batcher.read_into(game_phase_addr, &mut self.game_phase);
+            batcher.read_into(total_rounds_played_addr, &mut self.total_rounds_played);
+            batcher.read_into(overtime_playing_addr, &mut self.overtime_playing);
+            batcher.read_into(round_win_status_addr, &mut self.round_win_status);
+            batcher.read_into(round_time_addr, &mut self.round_time);
+            batcher.read_into(round_start_time_addr, &mut self.round_start_time);
+            batcher.read_into(map_has_bomb_target_addr, &mut map_has_bomb_target);
+            batcher.read_into(map_has_rescue_zone_addr, &mut map_has_rescue_zone);
+            batcher.read_into(hostages_remaining_addr, &mut self.hostages_remaining);
+            batcher.read_into(any_hostage_reached_addr, &mut any_hostage_reached);
+            batcher.read_into(ct_loss_streak_addr, &mut self.ct_loss_streak);
+            batcher.read_into(t_loss_streak_addr, &mut self.t_loss_streak);
+
+            // ConVars
+            if let Some(convar) = self.game_type_convar {
+                batcher.read_into(convar_value(convar), &mut self.game_type);
+            }
 
-                if self.bomb_planted_stamp.is_none() && !self.bomb.is_null() {
-                    self.bomb_planted_stamp = Some(Instant::now());
-                }
+            if let Some(convar) = self.game_mode_convar {
+                batcher.read_into(convar_value(convar), &mut self.game_mode);
+            }
 
-                if self.bomb_being_defused {
-                    if self.bomb_defuse_stamp.is_none() {
-                        self.bomb_defuse_stamp = Some(Instant::now())
-                    }
-                } else {
-                    if self.bomb_defuse_stamp.is_some() {
-                        self.bomb_defuse_stamp = None;
-                    }
-                }
+            if let Some(convar) = self.teammates_are_enemies_convar {
+                batcher.read_into(convar_value(convar), &mut teammates_are_enemies);
+            }
 
-            } else {
-                if self.bomb_planted_stamp.is_some() {
-                    self.bomb_planted_stamp = None;
-                }
+            self.teams.iter().zip(team_scores.iter_mut()).for_each(|((team, _), score)| {
+                batcher.read_into(*team + cs2dumper::client::C_Team::m_iScore, score);
+            });
+        }
 
-                if self.bomb_defuse_stamp.is_some() {
-                    self.bomb_defuse_stamp = None;
-                }
+        self.teams.iter().zip(team_scores).for_each(|((_, team_num), score)| {
+            match *team_num {
+                2 => self.t_score = score,
+                3 => self.ct_score = score,
+                _ => {}
             }
-        }
+        });
 
+        if self.bomb_planted {
+            let mut batcher = ctx.process.batcher();
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_flC4Blow, &mut self.bomb_c4_blow);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bBombDefused, &mut bomb_defused);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_flDefuseLength, &mut self.bomb_defuse_length);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_flDefuseCountDown, &mut self.bomb_defuse_countdown);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bHasExploded, &mut bomb_exploded);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_bBeingDefused, &mut bomb_being_defused);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_nBombSite, &mut self.bomb_site);
+            batcher.read_into(self.bomb + cs2dumper::client::C_PlantedC4::m_hBombDefuser, &mut self.bomb_defuser_handle);
+        } else if self.bomb_holder.is_some() && !self.c4_weapon.is_null() {
+            let mut batcher = ctx.process.batcher();
+            batcher.read_into(self.c4_weapon + cs2dumper::client::C_C4::m_bStartedArming, &mut c4_started_arming);
+            batcher.read_into(self.c4_weapon + cs2dumper::client::C_C4::m_fArmedTime, &mut self.c4_armed_time);
+        }
 
         let map_string = ctx.process.read_utf8_lossy(map_ptr.into(), 32).unwrap_or(String::from("<empty>"));
 
+        // Money and loss streaks don't carry over to the next match
+        if map_string != self.map {
+            self.economy = EconomyData::default();
+        }
+
         self.map = map_string;
         self.bomb_dropped = bomb_dropped != 0;
         self.bomb_planted = bomb_planted != 0;
@@ -249,6 +494,107 @@ impl CsData {
         self.bomb_being_defused = bomb_being_defused != 0;
         self.bomb_defused = bomb_defused != 0;
         self.freeze_period = freeze_period != 0;
+        self.warmup_period = warmup_period != 0;
+        self.c4_started_arming = c4_started_arming != 0;
+        self.teammates_are_enemies = teammates_are_enemies != 0;
+        self.map_has_bomb_target = map_has_bomb_target != 0;
+        self.map_has_rescue_zone = map_has_rescue_zone != 0;
+        self.any_hostage_reached = any_hostage_reached != 0;
+    }
+
+    pub fn update_c4_weapon(&mut self, ctx: &mut DmaCtx) {
+        if self.bomb_holder.is_none() {
+            self.c4_weapon = Address::null();
+            return;
+        }
+
+        self.c4_weapon = ctx.find_entities_by_designer_name(self.entity_list.into(), self.highest_index, "weapon_c4")
+            .first()
+            .copied()
+            .unwrap_or(Address::null());
+    }
+
+    /// Plant progress from 0 to 1 while the bomb holder is planting
+    pub fn plant_progress(&self) -> Option<f32> {
+        if self.bomb_holder.is_none() || !self.c4_started_arming {
+            return None;
+        }
+
+        let progress = 1.0 - (self.c4_armed_time - self.cur_time) / C4_ARM_TIME;
+        Some(progress.clamp(0.0, 1.0))
+    }
+
+    fn update_player_resource(&mut self, ctx: &mut DmaCtx) {
+        self.player_resource = ctx.find_entities_by_designer_name(self.entity_list.into(), self.highest_index, "cs_player_manager")
+            .first()
+            .copied()
+            .unwrap_or(Address::null());
+    }
+
+    pub fn update_bombsites(&mut self, ctx: &mut DmaCtx) {
+        if !self.map_has_bomb_target {
+            self.bombsite_centers.clear();
+            return;
+        }
+
+        self.update_player_resource(ctx);
+
+        if !self.player_resource.is_null() {
+            match ctx.get_bombsite_centers(self.player_resource) {
+                Ok(centers) => self.bombsite_centers = centers,
+                Err(e) => log::warn!("Failed to read bombsite centers: {}", e),
+            }
+        }
+    }
+
+    pub fn update_hostages(&mut self, ctx: &mut DmaCtx) {
+        if !self.map_has_rescue_zone {
+            self.hostages.clear();
+            self.rescue_zones.clear();
+            return;
+        }
+
+        self.hostages = ctx.find_entities_by_designer_name(self.entity_list.into(), self.highest_index, "hostage_entity");
+
+        self.update_player_resource(ctx);
+
+        if !self.player_resource.is_null() {
+            match ctx.get_rescue_zones(self.player_resource) {
+                Ok(zones) => self.rescue_zones = zones,
+                Err(e) => log::warn!("Failed to read hostage rescue zones: {}", e),
+            }
+        }
+    }
+
+    pub fn update_convars(&mut self, ctx: &mut DmaCtx) {
+        if self.game_type_convar.is_some() && self.game_mode_convar.is_some() && self.teammates_are_enemies_convar.is_some() {
+            return;
+        }
+
+        match ctx.find_convars(&["game_type", "game_mode", "mp_teammates_are_enemies"]) {
+            Ok(convars) => {
+                self.game_type_convar = convars[0];
+                self.game_mode_convar = convars[1];
+                self.teammates_are_enemies_convar = convars[2];
+            },
+            Err(e) => {
+                log::warn!("Failed to find convars: {}", e);
+            }
+        }
+    }
+
+    pub fn update_teams(&mut self, ctx: &mut DmaCtx) {
+        let teams = ctx.find_entities_by_designer_name(self.entity_list.into(), self.highest_index, "cs_team_manager");
+
+        let mut team_nums = vec![0i32; teams.len()];
+        {
+            let mut batcher = ctx.process.batcher();
+            teams.iter().zip(team_nums.iter_mut()).for_each(|(team, team_num)| {
+                batcher.read_into(*team + cs2dumper::client::C_BaseEntity::m_iTeamNum, team_num);
+            });
+        }
+
+        self.teams = teams.into_iter().zip(team_nums).collect();
     }
 
     pub fn update_pointers(&mut self, ctx: &mut DmaCtx) {
@@ -0,0 +1,147 @@
+use serde::{Serialize, Deserialize};
+
+use crate::enums::{BuyType, TeamID};
+
+const MAX_MONEY: i32 = 16000;
+
+const LOSS_BONUS_BASE: i32 = 1400;
+const LOSS_BONUS_STEP: i32 = 500;
+const LOSS_BONUS_MAX_STEPS: i32 = 4;
+
+/// Average money plus equipment per player needed for rifles, armor and utility
+const FULL_BUY_THRESHOLD: i32 = 4000;
+/// Average needed for at least armor and a better pistol or smg
+const FORCE_BUY_THRESHOLD: i32 = 2000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TeamEconomy {
+    /// Money of all players combined
+    pub money: i32,
+
+    /// Combined value of everything the players are carrying
+    #[serde(rename = "equipmentValue")]
+    pub equipment_value: i32,
+
+    #[serde(rename = "lossStreak")]
+    pub loss_streak: i32,
+
+    /// What every player gets if the team loses this round
+    #[serde(rename = "lossBonus")]
+    pub loss_bonus: i32,
+
+    pub buy: BuyType,
+
+    /// Team money next round if they lose, not counting kill rewards or anything spent
+    #[serde(rename = "predictedMinMoney")]
+    pub predicted_min_money: i32,
+
+    pub players: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct EconomyData {
+    pub ct: TeamEconomy,
+    pub t: TeamEconomy,
+}
+
+/// Money and equipment value of a single player
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerEconomy {
+    pub team: Option<TeamID>,
+    pub money: i32,
+    pub equipment_value: i32,
+}
+
+/// Loss bonus paid out for the next loss, given the current loss streak
+pub fn loss_bonus(loss_streak: i32) -> i32 {
+    LOSS_BONUS_BASE + LOSS_BONUS_STEP * loss_streak.clamp(0, LOSS_BONUS_MAX_STEPS)
+}
+
+pub fn classify(money: i32, equipment_value: i32, players: usize) -> BuyType {
+    if players == 0 {
+        return BuyType::Eco;
+    }
+
+    let average = (money + equipment_value) / players as i32;
+
+    if average >= FULL_BUY_THRESHOLD {
+        BuyType::Full
+    } else if average >= FORCE_BUY_THRESHOLD {
+        BuyType::Force
+    } else {
+        BuyType::Eco
+    }
+}
+
+fn team_economy(players: &[PlayerEconomy], team: TeamID, loss_streak: i32) -> TeamEconomy {
+    let bonus = loss_bonus(loss_streak);
+    let members: Vec<&PlayerEconomy> = players
+        .iter()
+        .filter(|player| player.team == Some(team))
+        .collect();
+
+    let money = members.iter().map(|player| player.money).sum();
+    let equipment_value = members.iter().map(|player| player.equipment_value).sum();
+    let predicted_min_money = members
+        .iter()
+        .map(|player| (player.money + bonus).min(MAX_MONEY))
+        .sum();
+
+    TeamEconomy {
+        money,
+        equipment_value,
+        loss_streak,
+        loss_bonus: bonus,
+        buy: classify(money, equipment_value, members.len()),
+        predicted_min_money,
+        players: members.len(),
+    }
+}
+
+pub fn compute(players: &[PlayerEconomy], ct_loss_streak: i32, t_loss_streak: i32) -> EconomyData {
+    EconomyData {
+        ct: team_economy(players, TeamID::CT, ct_loss_streak),
+        t: team_economy(players, TeamID::T, t_loss_streak),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loss_bonus_grows_and_caps() {
+        assert_eq!(loss_bonus(0), 1400);
+        assert_eq!(loss_bonus(1), 1900);
+        assert_eq!(loss_bonus(4), 3400);
+        assert_eq!(loss_bonus(7), 3400);
+        assert_eq!(loss_bonus(-1), 1400);
+    }
+
+    #[test]
+    fn classify_uses_average_per_player() {
+        assert_eq!(classify(20000, 0, 5), BuyType::Full);
+        assert_eq!(classify(10000, 0, 5), BuyType::Force);
+        assert_eq!(classify(5000, 2000, 5), BuyType::Eco);
+        assert_eq!(classify(4000, 16000, 5), BuyType::Full);
+    }
+
+    #[test]
+    fn classify_without_players_is_eco() {
+        assert_eq!(classify(16000, 0, 0), BuyType::Eco);
+    }
+
+    #[test]
+    fn predicted_money_is_capped() {
+        let players = [
+            PlayerEconomy { team: Some(TeamID::T), money: 15500, equipment_value: 0 },
+            PlayerEconomy { team: Some(TeamID::T), money: 0, equipment_value: 0 },
+            PlayerEconomy { team: Some(TeamID::CT), money: 800, equipment_value: 0 },
+        ];
+
+        let economy = compute(&players, 0, 2);
+        assert_eq!(economy.t.predicted_min_money, 16000 + 2400);
+        assert_eq!(economy.t.players, 2);
+        assert_eq!(economy.ct.predicted_min_money, 800 + 1400);
+    }
+}
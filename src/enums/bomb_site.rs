@@ -0,0 +1,7 @@
+/// Mirrors `m_nBombSite` of `C_PlantedC4`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, enum_primitive_derive::Primitive, serde::Serialize, serde::Deserialize)]
+pub enum BombSite {
+    A = 0,
+    B = 1
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+/// What a team can afford this round
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Default, PartialEq)]
+pub enum BuyType {
+    #[default]
+    Eco,
+    Force,
+    Full
+}
\ No newline at end of file
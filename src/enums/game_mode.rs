@@ -0,0 +1,28 @@
+/// Game mode as selected by the `game_type` and `game_mode` convars
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Default, PartialEq)]
+pub enum GameMode {
+    #[default]
+    Unknown,
+    Casual,
+    Competitive,
+    Wingman,
+    ArmsRace,
+    Demolition,
+    Deathmatch,
+    Custom
+}
+
+impl GameMode {
+    pub fn from_convars(game_type: i32, game_mode: i32) -> GameMode {
+        match (game_type, game_mode) {
+            (0, 0) => GameMode::Casual,
+            (0, 1) => GameMode::Competitive,
+            (0, 2) => GameMode::Wingman,
+            (1, 0) => GameMode::ArmsRace,
+            (1, 1) => GameMode::Demolition,
+            (1, 2) => GameMode::Deathmatch,
+            (3, _) => GameMode::Custom,
+            _ => GameMode::Unknown,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Default, PartialEq)]
+pub enum HostageState {
+    #[default]
+    Idle,
+    Following,
+    Rescued
+}
\ No newline at end of file
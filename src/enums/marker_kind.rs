@@ -0,0 +1,7 @@
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq)]
+pub enum MarkerKind {
+    /// Where a player died, kept until the round ends
+    Death,
+    /// Where a player was last seen alive before their data went stale
+    LastKnown
+}
\ No newline at end of file
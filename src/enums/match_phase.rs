@@ -0,0 +1,12 @@
+/// Mirrors `m_gamePhase` of `C_CSGameRules`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, enum_primitive_derive::Primitive, serde::Serialize, serde::Deserialize)]
+pub enum MatchPhase {
+    #[default]
+    Warmup = 0,
+    Standard = 1,
+    FirstHalf = 2,
+    SecondHalf = 3,
+    Halftime = 4,
+    MatchEnded = 5
+}
\ No newline at end of file
@@ -1,5 +1,21 @@
 mod teamid;
 mod player_type;
+mod round_phase;
+mod match_phase;
+mod perspective;
+mod game_mode;
+mod hostage_state;
+mod bomb_site;
+mod marker_kind;
+mod buy_type;
 
 pub use teamid::TeamID;
-pub use player_type::PlayerType;
\ No newline at end of file
+pub use player_type::PlayerType;
+pub use round_phase::RoundPhase;
+pub use match_phase::MatchPhase;
+pub use perspective::Perspective;
+pub use game_mode::GameMode;
+pub use hostage_state::HostageState;
+pub use bomb_site::BombSite;
+pub use marker_kind::MarkerKind;
+pub use buy_type::BuyType;
\ No newline at end of file
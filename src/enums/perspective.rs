@@ -0,0 +1,9 @@
+/// Side from which player types are computed.
+/// `Local` follows the local player, `CT` and `T` give a fixed view for casting or reviewing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+pub enum Perspective {
+    #[default]
+    Local,
+    CT,
+    T
+}
\ No newline at end of file
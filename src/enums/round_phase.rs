@@ -0,0 +1,8 @@
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, Default, PartialEq, clap::ValueEnum)]
+pub enum RoundPhase {
+    #[default]
+    Warmup,
+    FreezeTime,
+    Live,
+    Over
+}
\ No newline at end of file
@@ -1,6 +1,7 @@
 #[repr(i32)]
-#[derive(Debug, Eq, PartialEq, enum_primitive_derive::Primitive)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, enum_primitive_derive::Primitive, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
 pub enum TeamID {
+    #[value(skip)]
     Spectator = 1,
     T = 2,
     CT = 3
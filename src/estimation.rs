@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{maps::RadarPos, nav::{NavMesh, RUN_SPEED}, structs::Vec3};
+
+/// Walking distance the spread stops growing at, long lost enemies keep the widest spot instead of vanishing
+const MAX_SPREAD: f32 = 20.0 * RUN_SPEED;
+
+/// Most nav areas a single enemy is spread over, larger sets are thinned out evenly
+const MAX_AREAS: usize = 48;
+
+/// Smallest spot drawn for a freshly lost enemy
+const MIN_RADIUS: f32 = 64.0;
+
+/// How much the walking budget has to grow before a cached estimate is recomputed, about a quarter second of running
+const RECOMPUTE_STEP: f32 = 0.25 * RUN_SPEED;
+
+/// A blob of the heat layer, an enemy is somewhere inside it with probability `weight`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatPoint {
+    pub pos: Vec3,
+
+    /// Spread in units
+    pub radius: f32,
+
+    /// Weights of all points of a player add up to 1
+    pub weight: f32,
+
+    #[serde(rename = "playerName")]
+    pub player_name: String,
+
+    /// Position on the radar image, only sent if enabled
+    #[serde(rename = "radarPos", default, skip_serializing_if = "Option::is_none")]
+    pub radar_pos: Option<RadarPos>,
+}
+
+/// Walking distance an enemy could have covered in `age` seconds
+fn spread(age: f32) -> f32 {
+    (age * RUN_SPEED).min(MAX_SPREAD)
+}
+
+/// Spreads an enemy's last known position over everything reachable in `age` seconds, up to `MAX_SPREAD`.
+/// Uses the nav mesh if there is one, otherwise a circle limited by the running speed.
+pub fn estimate(player_name: &str, last_pos: Vec3, age: f32, nav: Option<&NavMesh>) -> Vec<HeatPoint> {
+    let budget = spread(age);
+
+    let reachable = nav
+        .map(|mesh| mesh.reachable(&last_pos, budget))
+        .unwrap_or_default();
+
+    if reachable.is_empty() {
+        return vec![HeatPoint {
+            pos: last_pos,
+            radius: budget.max(MIN_RADIUS),
+            weight: 1.0,
+            player_name: player_name.to_string(),
+            radar_pos: None,
+        }];
+    }
+
+    let step = reachable.len().div_ceil(MAX_AREAS);
+    let areas: Vec<_> = reachable.iter().step_by(step).collect();
+    let weight = 1.0 / areas.len() as f32;
+
+    areas
+        .into_iter()
+        .map(|(area, _)| HeatPoint {
+            pos: area.center,
+            radius: area.radius().max(MIN_RADIUS),
+            weight,
+            player_name: player_name.to_string(),
+            radar_pos: None,
+        })
+        .collect()
+}
+
+struct CachedEstimate {
+    last_pos: Vec3,
+    budget: f32,
+    points: Vec<HeatPoint>,
+}
+
+/// Last estimate per lost enemy, so the nav mesh search doesn't run for every enemy on every tick
+#[derive(Default)]
+pub struct HeatCache {
+    estimates: HashMap<String, CachedEstimate>,
+}
+
+impl HeatCache {
+    /// Heat for every lost enemy as `(name, last position, age)`. Estimates are reused until the enemy is seen
+    /// somewhere else or the spread grew by `RECOMPUTE_STEP`, enemies that aren't passed in are forgotten.
+    pub fn update<'a>(&mut self, lost: impl IntoIterator<Item = (&'a str, Vec3, f32)>, nav: Option<&NavMesh>) -> Vec<HeatPoint> {
+        let mut estimates = HashMap::new();
+
+        for (name, last_pos, age) in lost {
+            let budget = spread(age);
+
+            let estimate = match self.estimates.remove(name) {
+                Some(cached) if cached.last_pos == last_pos && budget - cached.budget < RECOMPUTE_STEP => cached,
+                _ => CachedEstimate { last_pos, budget, points: estimate(name, last_pos, age, nav) },
+            };
+
+            estimates.insert(name.to_string(), estimate);
+        }
+
+        self.estimates = estimates;
+        self.estimates.values().flat_map(|estimate| estimate.points.iter().cloned()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nav::NavArea;
+
+    use super::*;
+
+    /// `count` 100 unit squares in a row along x, each connected to its neighbours
+    fn corridor(count: u32) -> NavMesh {
+        let areas = (0..count)
+            .map(|i| {
+                let x = i as f32 * 100.0;
+                let corners = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]
+                    .map(|(dx, y)| Vec3 { x: x + dx, y, z: 0.0 })
+                    .to_vec();
+                let connections = [i.checked_sub(1), (i + 1 < count).then_some(i + 1)].into_iter().flatten().collect();
+
+                NavArea::new(i, corners, connections)
+            })
+            .collect();
+
+        NavMesh::new(35, areas)
+    }
+
+    fn start() -> Vec3 {
+        Vec3 { x: 50.0, y: 50.0, z: 0.0 }
+    }
+
+    #[test]
+    fn circle_without_mesh() {
+        let points = estimate("a", start(), 2.0, None);
+
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].radius, 2.0 * RUN_SPEED);
+        assert_eq!(points[0].weight, 1.0);
+        assert_eq!(estimate("a", start(), 0.0, None)[0].radius, MIN_RADIUS);
+    }
+
+    #[test]
+    fn spread_is_capped() {
+        assert_eq!(estimate("a", start(), 300.0, None)[0].radius, MAX_SPREAD);
+
+        let mesh = corridor(200);
+        let far = mesh.reachable(&start(), MAX_SPREAD + 1000.0).len();
+        let capped = mesh.reachable(&start(), spread(300.0)).len();
+        assert!(capped < far);
+        assert!(!estimate("a", start(), 300.0, Some(&mesh)).is_empty());
+    }
+
+    #[test]
+    fn weights_add_up_to_one() {
+        let mesh = corridor(200);
+
+        for age in [0.0, 1.0, 5.0, 30.0] {
+            let points = estimate("a", start(), age, Some(&mesh));
+            let total: f32 = points.iter().map(|point| point.weight).sum();
+
+            assert!(points.len() <= MAX_AREAS);
+            assert!((total - 1.0).abs() < 1e-4, "weights add up to {total} at age {age}");
+        }
+    }
+
+    #[test]
+    fn cache_recomputes_on_growth_and_forgets_found_enemies() {
+        let mut cache = HeatCache::default();
+
+        let first = cache.update([("a", start(), 1.0)], None);
+        let reused = cache.update([("a", start(), 1.1)], None);
+        assert_eq!(reused[0].radius, first[0].radius);
+
+        let grown = cache.update([("a", start(), 2.0)], None);
+        assert_eq!(grown[0].radius, 2.0 * RUN_SPEED);
+
+        let moved = cache.update([("a", Vec3 { x: 500.0, y: 0.0, z: 0.0 }, 2.0)], None);
+        assert_eq!(moved[0].pos.x, 500.0);
+
+        assert!(cache.update(Vec::<(&str, Vec3, f32)>::new(), None).is_empty());
+        assert!(cache.estimates.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, time::{SystemTime, UNIX_EPOCH}};
+
+use memflow::types::Address;
+use serde::{Serialize, Deserialize};
+use tokio::sync::broadcast;
+
+use crate::{dma::threaddata::CsData, enums::TeamID};
+
+pub type EventSender = broadcast::Sender<GameEvent>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GameEventKind {
+    PlayerDeath {
+        #[serde(rename = "playerName")]
+        player_name: String,
+    },
+    PlayerSpawn {
+        #[serde(rename = "playerName")]
+        player_name: String,
+    },
+    BombPickup {
+        #[serde(rename = "playerName")]
+        player_name: Option<String>,
+    },
+    BombDrop,
+    BombPlant,
+    BombDefuseStart,
+    BombDefused,
+    BombExploded,
+    RoundStart {
+        round: i32,
+    },
+    RoundEnd {
+        round: i32,
+        /// None for a draw
+        winner: Option<TeamID>,
+    },
+    MapChange {
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEvent {
+    pub tick: i32,
+
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+
+    #[serde(flatten)]
+    pub kind: GameEventKind,
+}
+
+impl GameEvent {
+    pub fn new(tick: i32, kind: GameEventKind) -> GameEvent {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        GameEvent { tick, timestamp, kind }
+    }
+}
+
+/// The parts of `CsData` events are derived from, so the loop doesn't have to keep a full copy around
+#[derive(Debug, Clone, Default)]
+pub struct EventState {
+    tick: i32,
+    map: String,
+    round_start_count: u8,
+    total_rounds_played: i32,
+    round_win_status: i32,
+
+    /// Name and alive state per controller
+    players: HashMap<Address, (String, bool)>,
+
+    bomb_held: bool,
+    bomb_holder_name: Option<String>,
+    bomb_dropped: bool,
+    bomb_planted: bool,
+    bomb_being_defused: bool,
+    bomb_defused: bool,
+    bomb_exploded: bool,
+}
+
+impl EventState {
+    pub fn new(data: &CsData) -> EventState {
+        let bomb_holder_name = data.bomb_holder
+            .and_then(|pawn| controller_of(data, pawn))
+            .and_then(|controller| data.player_status.get(&controller))
+            .map(|status| status.name.clone());
+
+        EventState {
+            tick: data.tick_count,
+            map: data.map.clone(),
+            round_start_count: data.round_start_count,
+            total_rounds_played: data.total_rounds_played,
+            round_win_status: data.round_win_status,
+            players: data.player_status
+                .iter()
+                .map(|(controller, status)| (*controller, (status.name.clone(), status.alive)))
+                .collect(),
+            bomb_held: data.bomb_holder.is_some(),
+            bomb_holder_name,
+            bomb_dropped: data.bomb_dropped,
+            bomb_planted: data.bomb_planted,
+            bomb_being_defused: data.bomb_being_defused,
+            bomb_defused: data.bomb_defused,
+            bomb_exploded: data.bomb_exploded,
+        }
+    }
+
+    /// True if `data` is from a different tick or map, nothing else can produce events
+    pub fn is_outdated(&self, data: &CsData) -> bool {
+        self.tick != data.tick_count || self.map != data.map
+    }
+}
+
+/// Side that won the round from `m_iRoundWinStatus`, None for a draw or no winner yet
+fn round_winner(status: i32) -> Option<TeamID> {
+    match status {
+        2 => Some(TeamID::T),
+        3 => Some(TeamID::CT),
+        _ => None,
+    }
+}
+
+/// Compares two consecutive states and returns the events that happened in between
+pub fn diff(prev: &EventState, cur: &EventState) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+
+    if prev.map != cur.map {
+        events.push(GameEventKind::MapChange {
+            from: prev.map.clone(),
+            to: cur.map.clone()
+        });
+
+        // Everything else is meaningless across a map change
+        return events.into_iter().map(|kind| GameEvent::new(cur.tick, kind)).collect();
+    }
+
+    // Round
+    if prev.round_start_count != cur.round_start_count {
+        events.push(GameEventKind::RoundStart { round: cur.total_rounds_played + 1 });
+    }
+
+    if prev.round_win_status == 0 && cur.round_win_status != 0 {
+        events.push(GameEventKind::RoundEnd {
+            round: cur.total_rounds_played,
+            winner: round_winner(cur.round_win_status)
+        });
+    }
+
+    // Players
+    for (controller, (name, alive)) in &cur.players {
+        let was_alive = prev.players
+            .get(controller)
+            .map(|(_, prev_alive)| *prev_alive);
+
+        match (was_alive, *alive) {
+            (Some(true), false) => events.push(GameEventKind::PlayerDeath { player_name: name.clone() }),
+            (Some(false), true) => events.push(GameEventKind::PlayerSpawn { player_name: name.clone() }),
+            _ => {}
+        }
+    }
+
+    // Bomb
+    if !prev.bomb_held && cur.bomb_held {
+        events.push(GameEventKind::BombPickup { player_name: cur.bomb_holder_name.clone() });
+    }
+
+    if !prev.bomb_dropped && cur.bomb_dropped {
+        events.push(GameEventKind::BombDrop);
+    }
+
+    if !prev.bomb_planted && cur.bomb_planted {
+        events.push(GameEventKind::BombPlant);
+    }
+
+    if !prev.bomb_being_defused && cur.bomb_being_defused {
+        events.push(GameEventKind::BombDefuseStart);
+    }
+
+    if !prev.bomb_defused && cur.bomb_defused {
+        events.push(GameEventKind::BombDefused);
+    }
+
+    if !prev.bomb_exploded && cur.bomb_exploded {
+        events.push(GameEventKind::BombExploded);
+    }
+
+    events.into_iter().map(|kind| GameEvent::new(cur.tick, kind)).collect()
+}
+
+fn controller_of(data: &CsData, pawn: Address) -> Option<Address> {
+    if pawn == Address::from(data.local_pawn) {
+        return Some(data.local.into());
+    }
+
+    data.players
+        .iter()
+        .find(|(_, p)| *p == pawn)
+        .map(|(controller, _)| *controller)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(prev: &EventState, cur: &EventState) -> Vec<GameEventKind> {
+        diff(prev, cur).into_iter().map(|event| event.kind).collect()
+    }
+
+    #[test]
+    fn round_end_winner() {
+        let prev = EventState { total_rounds_played: 3, ..Default::default() };
+
+        for (status, winner) in [(1, None), (2, Some(TeamID::T)), (3, Some(TeamID::CT))] {
+            let cur = EventState { round_win_status: status, ..prev.clone() };
+            assert!(matches!(&kinds(&prev, &cur)[..], [GameEventKind::RoundEnd { round: 3, winner: w }] if *w == winner));
+        }
+    }
+
+    #[test]
+    fn deaths_and_spawns() {
+        let controller = Address::from(0x1000u64);
+        let alive = EventState { players: HashMap::from([(controller, ("a".to_string(), true))]), ..Default::default() };
+        let dead = EventState { players: HashMap::from([(controller, ("a".to_string(), false))]), ..Default::default() };
+
+        assert!(matches!(&kinds(&alive, &dead)[..], [GameEventKind::PlayerDeath { player_name }] if player_name == "a"));
+        assert!(matches!(&kinds(&dead, &alive)[..], [GameEventKind::PlayerSpawn { player_name }] if player_name == "a"));
+        assert!(kinds(&alive, &alive).is_empty());
+    }
+
+    #[test]
+    fn map_change_hides_everything_else() {
+        let prev = EventState { map: "de_mirage".to_string(), ..Default::default() };
+        let cur = EventState { map: "de_nuke".to_string(), bomb_planted: true, round_start_count: 1, ..Default::default() };
+
+        assert!(matches!(&kinds(&prev, &cur)[..], [GameEventKind::MapChange { from, to }] if from == "de_mirage" && to == "de_nuke"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use image::Rgba;
+
+use crate::{comms::EntityData, enums::{RoundPhase, TeamID}, maps::{self, MapRegistry, RADAR_IMAGE_SIZE}, recording};
+
+/// Radius of the blob a single sample adds, in pixels of a 1024px radar
+const SPLAT_RADIUS: f32 = 12.0;
+
+/// Heat below this fraction of the maximum is left transparent
+const MIN_HEAT: f32 = 0.02;
+
+const MAX_ALPHA: f32 = 0.75;
+
+/// Which samples go into the heatmap, unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct HeatmapFilter {
+    pub team: Option<TeamID>,
+    pub player: Option<String>,
+    pub phase: Option<RoundPhase>,
+    pub round: Option<i32>,
+
+    /// 1-based, numbered across all recordings in order
+    pub session: Option<usize>,
+
+    /// Floor of a multi-level map, see `MapInfo::layer_at`
+    pub layer: Option<String>,
+}
+
+/// Accumulates player positions from recordings and renders them onto the map's radar image
+pub fn generate(recordings: &[PathBuf], map: &str, filter: &HeatmapFilter, web_path: &Path, output: &Path) -> anyhow::Result<()> {
+    let registry = MapRegistry::new(web_path);
+    let info = registry
+        .get(map)
+        .and_then(|entry| entry.info.clone())
+        .ok_or_else(|| anyhow::anyhow!("no usable radar json for {}", map))?;
+
+    if let Some(layer) = &filter.layer {
+        if !info.layers.iter().any(|candidate| &candidate.name == layer) {
+            let names: Vec<&str> = info.layers.iter().map(|layer| layer.name.as_str()).collect();
+            return Err(anyhow::anyhow!("{} has no layer {}, it has [{}]", map, layer, names.join(", ")));
+        }
+    }
+
+    let mut image = image::open(registry.layer_image_path(map, filter.layer.as_deref()))?.to_rgba8();
+    let (width, height) = image.dimensions();
+    let scale = width as f32 / RADAR_IMAGE_SIZE;
+
+    let kernel = Kernel::new(SPLAT_RADIUS * scale);
+    let mut heat = vec![0.0f32; (width * height) as usize];
+    let mut samples = 0usize;
+
+    // Sessions are numbered across recordings, so each file continues where the last one stopped
+    let mut session_offset = 0;
+
+    for path in recordings {
+        let mut last_session = 0;
+
+        for (session, frame) in recording::sessions(recording::frames(path)?) {
+            last_session = session;
+
+            if filter.session.is_some_and(|wanted| session_offset + session != wanted) {
+                continue;
+            }
+
+            if frame.get_map_name() != map {
+                continue;
+            }
+
+            if filter.phase.is_some_and(|phase| frame.get_match_data().round_phase != phase) {
+                continue;
+            }
+
+            if filter.round.is_some_and(|round| frame.get_match_data().round != round) {
+                continue;
+            }
+
+            for entity in frame.get_entities() {
+                let EntityData::Player(player) = entity else {
+                    continue;
+                };
+
+                if filter.team.is_some_and(|team| player.team() != Some(team)) {
+                    continue;
+                }
+
+                if filter.player.as_ref().is_some_and(|name| !name.eq_ignore_ascii_case(player.player_name())) {
+                    continue;
+                }
+
+                if filter.layer.is_some() && info.layer_at(player.pos().z) != filter.layer.as_deref() {
+                    continue;
+                }
+
+                let radar = maps::world_to_radar(&info, &player.pos());
+                if kernel.splat(&mut heat, width, height, radar.x * scale, radar.y * scale) {
+                    samples += 1;
+                }
+            }
+        }
+
+        session_offset += last_session;
+    }
+
+    let max = heat.iter().copied().fold(0.0, f32::max);
+
+    if samples == 0 || max <= 0.0 {
+        return Err(anyhow::anyhow!("no positions on {} matched the filter", map));
+    }
+
+    for (idx, value) in heat.iter().enumerate() {
+        let t = value / max;
+        if t < MIN_HEAT {
+            continue;
+        }
+
+        let x = idx as u32 % width;
+        let y = idx as u32 / width;
+        blend(image.get_pixel_mut(x, y), color(t), t.sqrt() * MAX_ALPHA);
+    }
+
+    image.save(output)?;
+    println!("Rendered {} positions onto {}", samples, output.display());
+
+    Ok(())
+}
+
+/// Precomputed gaussian that gets added around every sample
+struct Kernel {
+    radius: i32,
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    fn new(radius: f32) -> Kernel {
+        let radius = radius.ceil().max(1.0) as i32;
+        let sigma = radius as f32 / 2.5;
+        let size = 2 * radius + 1;
+
+        let weights = (0..size * size)
+            .map(|idx| {
+                let dx = (idx % size - radius) as f32;
+                let dy = (idx / size - radius) as f32;
+                (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+
+        Kernel { radius, weights }
+    }
+
+    /// Adds the kernel around `x`, `y`, returns false if none of it landed on the image
+    fn splat(&self, heat: &mut [f32], width: u32, height: u32, x: f32, y: f32) -> bool {
+        let (cx, cy) = (x.round() as i32, y.round() as i32);
+        let size = 2 * self.radius + 1;
+        let mut hit = false;
+
+        for dy in -self.radius..=self.radius {
+            for dx in -self.radius..=self.radius {
+                let (px, py) = (cx + dx, cy + dy);
+                if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+                    continue;
+                }
+
+                let weight = self.weights[((dy + self.radius) * size + dx + self.radius) as usize];
+                heat[(py as u32 * width + px as u32) as usize] += weight;
+                hit = true;
+            }
+        }
+
+        hit
+    }
+}
+
+/// Blue to red through cyan, green and yellow
+fn color(t: f32) -> [f32; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 255.0],
+        [0.0, 255.0, 255.0],
+        [0.0, 255.0, 0.0],
+        [255.0, 255.0, 0.0],
+        [255.0, 0.0, 0.0],
+    ];
+
+    let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let idx = (scaled.floor() as usize).min(STOPS.len() - 2);
+    let frac = scaled - idx as f32;
+
+    let (from, to) = (STOPS[idx], STOPS[idx + 1]);
+    [0, 1, 2].map(|c| from[c] + (to[c] - from[c]) * frac)
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: [f32; 3], alpha: f32) {
+    for (channel, color) in pixel.0.iter_mut().zip(color) {
+        *channel = (*channel as f32 * (1.0 - alpha) + color * alpha).round() as u8;
+    }
+
+    // Radar images are transparent outside the map, heat there should still show up
+    pixel.0[3] = pixel.0[3].max((alpha * 255.0).round() as u8);
+}
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use memflow::types::Address;
+
+use crate::{structs::Vec3, enums::{MarkerKind, PlayerType, TeamID}, comms::MarkerData};
+
+/// Minimum distance between two trail points, keeps the trail small while standing still
+const TRAIL_MIN_DISTANCE: f32 = 8.0;
+
+#[derive(Debug, Clone)]
+struct PlayerHistory {
+    name: String,
+    player_type: PlayerType,
+    team: Option<TeamID>,
+    pos: Vec3,
+    yaw: f32,
+    last_seen: f32,
+    dead: bool,
+    trail: VecDeque<(f32, Vec3)>,
+}
+
+/// Remembers where players were, so they don't just vanish from the radar when they die or their reads fail
+#[derive(Debug, Clone)]
+pub struct History {
+    trail_seconds: f32,
+    players: HashMap<Address, PlayerHistory>,
+}
+
+impl History {
+    pub fn new(trail_seconds: f32) -> History {
+        History {
+            trail_seconds,
+            players: HashMap::new(),
+        }
+    }
+
+    /// Records an alive player at game time `time`
+    pub fn record(&mut self, controller: Address, name: &str, player_type: PlayerType, team: Option<TeamID>, pos: Vec3, yaw: f32, time: f32) {
+        let entry = self.players
+            .entry(controller)
+            .or_insert_with(|| PlayerHistory {
+                name: name.to_string(),
+                player_type,
+                team,
+                pos,
+                yaw,
+                last_seen: time,
+                dead: false,
+                trail: VecDeque::new(),
+            });
+
+        // Respawned, the old trail leads from the death spot
+        if entry.dead {
+            entry.trail.clear();
+        }
+
+        entry.name = name.to_string();
+        entry.player_type = player_type;
+        entry.team = team;
+        entry.pos = pos;
+        entry.yaw = yaw;
+        entry.last_seen = time;
+        entry.dead = false;
+
+        if self.trail_seconds <= 0.0 {
+            return;
+        }
+
+        let moved = entry.trail
+            .back()
+            .map(|(_, last)| last.distance(&pos) >= TRAIL_MIN_DISTANCE)
+            .unwrap_or(true);
+
+        if moved {
+            entry.trail.push_back((time, pos));
+        }
+
+        while let Some((stamp, _)) = entry.trail.front() {
+            if time - stamp > self.trail_seconds || *stamp > time {
+                entry.trail.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Marks a player as dead, they stay on the radar as a death marker until [`History::clear`]
+    pub fn mark_dead(&mut self, controller: Address) {
+        if let Some(entry) = self.players.get_mut(&controller) {
+            entry.dead = true;
+        }
+    }
+
+    pub fn trail(&self, controller: Address) -> Vec<Vec3> {
+        self.players
+            .get(&controller)
+            .map(|entry| entry.trail.iter().map(|(_, pos)| *pos).collect())
+            .unwrap_or_default()
+    }
+
+    /// Markers for every known player that wasn't in this frame
+    pub fn markers(&self, seen: &HashSet<Address>, time: f32) -> Vec<MarkerData> {
+        self.players
+            .iter()
+            .filter(|(controller, _)| !seen.contains(controller))
+            .map(|(_, entry)| {
+                let kind = if entry.dead {
+                    MarkerKind::Death
+                } else {
+                    MarkerKind::LastKnown
+                };
+
+                MarkerData::new(
+                    entry.pos,
+                    entry.yaw,
+                    kind,
+                    entry.name.clone(),
+                    entry.player_type,
+                    entry.team,
+                    (time - entry.last_seen).max(0.0)
+                )
+            })
+            .collect()
+    }
+
+    /// Forgets everything, called when a new round starts
+    pub fn clear(&mut self) {
+        self.players.clear();
+    }
+}
@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, Command, MapsCommand};
 use comms::RadarData;
-use tokio::sync::RwLock;
+use smoothing::Smoother;
+use maps::MapRegistry;
+use nav::NavStore;
+use tokio::sync::{broadcast, RwLock};
 
 mod cli;
 mod structs;
@@ -15,6 +18,18 @@ mod websocket;
 
 mod pattern;
 mod money_reveal;
+mod events;
+mod smoothing;
+mod history;
+mod subscription;
+mod economy;
+mod maps;
+mod summary;
+mod nav;
+mod estimation;
+mod recording;
+mod heatmap;
+mod render;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -25,16 +40,66 @@ async fn main() -> anyhow::Result<()> {
         .init()
         .expect("Initializing logger");
 
+    if let Some(command) = &cli.command {
+        return match command {
+            Command::Maps { command: MapsCommand::Import { cs2_dir, overwrite } } => {
+                maps::import(cs2_dir, &cli.web_path, *overwrite)
+            },
+            Command::Heatmap { recordings, map, team, player, phase, round, session, layer, output } => {
+                let filter = heatmap::HeatmapFilter {
+                    team: *team,
+                    player: player.clone(),
+                    phase: *phase,
+                    round: *round,
+                    session: *session,
+                    layer: layer.clone(),
+                };
+
+                let output = output.clone().unwrap_or_else(|| format!("heatmap_{map}.png").into());
+                heatmap::generate(recordings, map, &filter, &cli.web_path, &output)
+            },
+            Command::Render { recording, round, session, size, fps, output } => {
+                let output = output.clone().unwrap_or_else(|| format!("round_{round}.gif").into());
+                let size = (*size).clamp(render::MIN_SIZE, render::MAX_SIZE);
+                render::export_round(recording, *round, *session, &cli.web_path, size, *fps, &output)
+            },
+        };
+    }
+
+    let tokens = match &cli.tokens {
+        Some(path) => Some(subscription::load_tokens(path)?),
+        None => None,
+    };
+
     let radar_data = Arc::new(
         RwLock::new(
             RadarData::empty(0)
         )
     );
 
+    radar_data.write().await.perspective = cli.perspective;
+
+    let (event_tx, _) = broadcast::channel(256);
+
+    let maps = Arc::new(RwLock::new(MapRegistry::new(&cli.web_path)));
+    tokio::spawn(maps::watch(maps.clone(), radar_data.clone()));
+
+    if let Some(path) = &cli.record {
+        tokio::spawn(recording::record(path.clone(), radar_data.clone()));
+    }
+
+    let nav_dir = cli.nav_dir.clone().unwrap_or_else(|| cli.web_path.join("assets").join("nav"));
+    let navs = Arc::new(RwLock::new(NavStore::new(&nav_dir)));
+
+    let smoother = cli.smoothing.then(|| Smoother::new(cli.prediction_ms));
+
     let radar_clone = radar_data.clone();
+    let maps_clone = maps.clone();
+    let heat_navs = cli.heat.then(|| navs.clone());
+    let event_clone = event_tx.clone();
 
     let dma_handle = tokio::spawn(async move {
-        if let Err(err) = dma::run(radar_clone, cli.connector, cli.pcileech_device, cli.skip_version).await {
+        if let Err(err) = dma::run(radar_clone, event_clone, smoother, cli.trail_seconds, cli.fog_of_war, maps_clone, cli.radar_pos, cli.summary, heat_navs, cli.connector, cli.pcileech_device, cli.skip_version).await {
             log::error!("Error in dma thread: [{}]", err.to_string());
         } else {
             println!("CS2 Process exited, exiting program...")
@@ -53,7 +118,7 @@ async fn main() -> anyhow::Result<()> {
             println!("launched webserver at {}", address);
         }
 
-        if let Err(err) = websocket::run(web_path, port, radar_data).await {
+        if let Err(err) = websocket::run(web_path, port, radar_data, event_tx, tokens, maps, navs).await {
             log::error!("Error in ws server: [{}]", err.to_string());
         }
     });
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use super::{keyvalues::{self, KeyValues}, MapInfo, MapLayer, IMAGE_DIR, IMAGE_SUFFIX, JSON_DIR};
+
+/// Image formats the `image` crate can read, tried in this order
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "tga", "jpg", "jpeg"];
+
+/// Where the overview files live relative to the path the user gave us
+const OVERVIEW_DIRS: [&str; 4] = ["game/csgo/resource/overviews", "csgo/resource/overviews", "resource/overviews", ""];
+
+/// Where extracted radar images usually end up, relative to the `csgo` directory
+const RADAR_IMAGE_DIRS: [&str; 2] = ["panorama/images/overheadmaps", "resource/overviews"];
+
+/// Imports every overview in a CS2 install into the web assets directory
+pub fn import(cs2_dir: &Path, web_path: &Path, overwrite: bool) -> anyhow::Result<()> {
+    let overview_dir = OVERVIEW_DIRS
+        .iter()
+        .map(|dir| cs2_dir.join(dir))
+        .find(|dir| has_overviews(dir))
+        .ok_or_else(|| anyhow::anyhow!("no resource/overviews/*.txt found in {}", cs2_dir.display()))?;
+
+    // <csgo>/resource/overviews
+    let csgo_dir = overview_dir
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(&overview_dir)
+        .to_path_buf();
+
+    let image_dirs: Vec<PathBuf> = RADAR_IMAGE_DIRS
+        .iter()
+        .map(|dir| csgo_dir.join(dir))
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+    let assets = web_path.join("assets");
+    std::fs::create_dir_all(assets.join(JSON_DIR))?;
+    std::fs::create_dir_all(assets.join(IMAGE_DIR))?;
+
+    let mut imported = 0;
+    let mut skipped = 0;
+
+    for entry in std::fs::read_dir(&overview_dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "txt") {
+            continue;
+        }
+
+        match import_overview(&path, &image_dirs, &assets, overwrite) {
+            Ok(Some(Imported::Written(name))) => {
+                println!("Imported {}", name);
+                imported += 1;
+            },
+            Ok(Some(Imported::Existing(name))) => {
+                println!("Skipped {}, it already exists (use --overwrite to replace it)", name);
+                skipped += 1;
+            },
+            Ok(None) => {},
+            Err(e) => println!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    println!("Imported {} maps into {}, skipped {} existing", imported, assets.display(), skipped);
+    Ok(())
+}
+
+fn has_overviews(dir: &Path) -> bool {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .any(|entry| entry.path().extension().is_some_and(|ext| ext == "txt"))
+        })
+        .unwrap_or(false)
+}
+
+enum Imported {
+    Written(String),
+    /// The json was already there and `--overwrite` wasn't given
+    Existing(String),
+}
+
+/// Returns None if the file isn't a radar overview
+fn import_overview(path: &Path, image_dirs: &[PathBuf], assets: &Path, overwrite: bool) -> anyhow::Result<Option<Imported>> {
+    let content = std::fs::read_to_string(path)?;
+    let document = keyvalues::parse(&content).with_context(|| "invalid KeyValues")?;
+
+    // The whole file is a single section named after the map
+    let Some((name, overview)) = document.children().first() else {
+        return Ok(None);
+    };
+
+    let Some(info) = parse_overview(name, overview) else {
+        return Ok(None);
+    };
+
+    let json_path = assets.join(JSON_DIR).join(format!("{name}.json"));
+    let write = overwrite || !json_path.exists();
+    if write {
+        std::fs::write(&json_path, serde_json::to_string_pretty(&info)?)?;
+    }
+
+    convert_image(name, &format!("{name}{IMAGE_SUFFIX}"), image_dirs, assets, overwrite);
+
+    for layer in &info.layers {
+        if let Some(image) = &layer.image {
+            convert_image(&format!("{name}_{}", layer.name), image, image_dirs, assets, overwrite);
+        }
+    }
+
+    if write {
+        Ok(Some(Imported::Written(name.clone())))
+    } else {
+        Ok(Some(Imported::Existing(name.clone())))
+    }
+}
+
+fn parse_overview(name: &str, overview: &KeyValues) -> Option<MapInfo> {
+    let layers = overview
+        .get("verticalsections")
+        .map(|sections| {
+            sections
+                .children()
+                .iter()
+                .filter_map(|(section, bounds)| {
+                    Some(MapLayer {
+                        name: section.clone(),
+                        altitude_min: bounds.get_f32("AltitudeMin")?,
+                        altitude_max: bounds.get_f32("AltitudeMax")?,
+                        // The default section uses the map's main image
+                        image: (!section.eq_ignore_ascii_case("default"))
+                            .then(|| format!("{name}_{section}{IMAGE_SUFFIX}")),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(MapInfo {
+        pos_x: overview.get_f32("pos_x")?,
+        pos_y: overview.get_f32("pos_y")?,
+        scale: overview.get_f32("scale")?,
+        rotate: overview.get_f32("rotate").unwrap_or(0.0) as i32,
+        zoom: overview.get_f32("zoom").unwrap_or(0.0),
+        layers,
+        zones: Vec::new(),
+    })
+}
+
+/// Converts `<stem>_radar_psd.<ext>` or `<stem>_radar.<ext>` into the png the web client expects
+fn convert_image(stem: &str, target: &str, image_dirs: &[PathBuf], assets: &Path, overwrite: bool) {
+    let target_path = assets.join(IMAGE_DIR).join(target);
+    if target_path.exists() && !overwrite {
+        return;
+    }
+
+    let candidates = [format!("{stem}_radar_psd"), format!("{stem}_radar")];
+
+    let source = image_dirs
+        .iter()
+        .flat_map(|dir| candidates.iter().map(move |candidate| dir.join(candidate)))
+        .flat_map(|base| IMAGE_EXTENSIONS.iter().map(move |ext| base.with_extension(ext)))
+        .find(|path| path.is_file());
+
+    let Some(source) = source else {
+        let compiled = image_dirs
+            .iter()
+            .flat_map(|dir| candidates.iter().map(move |candidate| dir.join(format!("{candidate}.vtex_c"))))
+            .any(|path| path.is_file());
+
+        if compiled {
+            println!("  {} is only available as a compiled .vtex_c texture, extract it to png first (e.g. with Source 2 Viewer)", stem);
+        } else {
+            println!("  No radar image found for {}", stem);
+        }
+        return;
+    };
+
+    match image::open(&source).and_then(|image| image.save(&target_path)) {
+        Ok(()) => {},
+        Err(e) => println!("  Failed to convert {}: {}", source.display(), e),
+    }
+}
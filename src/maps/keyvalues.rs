@@ -0,0 +1,203 @@
+use std::{iter::Peekable, str::Chars};
+
+/// A node of a Valve KeyValues (VDF) document
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyValues {
+    Value(String),
+    Section(Vec<(String, KeyValues)>),
+}
+
+impl KeyValues {
+    /// Looks up a child by key, case insensitive like the game does it
+    pub fn get(&self, key: &str) -> Option<&KeyValues> {
+        match self {
+            KeyValues::Section(children) => children
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            KeyValues::Value(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            KeyValues::Value(value) => Some(value),
+            KeyValues::Section(_) => None,
+        }
+    }
+
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get(key)?.as_str()?.trim().parse().ok()
+    }
+
+    pub fn children(&self) -> &[(String, KeyValues)] {
+        match self {
+            KeyValues::Section(children) => children,
+            KeyValues::Value(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                },
+                Some('/') => {
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+                    if lookahead.peek() != Some(&'/') {
+                        break;
+                    }
+
+                    // Comments run until the end of the line
+                    for c in self.chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                },
+                _ => break,
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> anyhow::Result<Option<Token>> {
+        self.skip_whitespace_and_comments();
+
+        let Some(c) = self.chars.next() else {
+            return Ok(None);
+        };
+
+        match c {
+            '{' => Ok(Some(Token::Open)),
+            '}' => Ok(Some(Token::Close)),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match self.chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(escaped) => value.push(escaped),
+                            None => return Err(anyhow::anyhow!("unterminated string")),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(anyhow::anyhow!("unterminated string")),
+                    }
+                }
+                Ok(Some(Token::String(value)))
+            },
+            c => {
+                let mut value = String::from(c);
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    self.chars.next();
+                }
+                Ok(Some(Token::String(value)))
+            },
+        }
+    }
+}
+
+fn parse_section(tokenizer: &mut Tokenizer, nested: bool) -> anyhow::Result<Vec<(String, KeyValues)>> {
+    let mut children = Vec::new();
+
+    loop {
+        let key = match tokenizer.next_token()? {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(children),
+            None if !nested => return Ok(children),
+            Some(token) => return Err(anyhow::anyhow!("unexpected {:?}", token)),
+            None => return Err(anyhow::anyhow!("unexpected end of file")),
+        };
+
+        let value = match tokenizer.next_token()? {
+            Some(Token::String(value)) => KeyValues::Value(value),
+            Some(Token::Open) => KeyValues::Section(parse_section(tokenizer, true)?),
+            Some(Token::Close) => return Err(anyhow::anyhow!("missing value for \"{}\"", key)),
+            None => return Err(anyhow::anyhow!("unexpected end of file after \"{}\"", key)),
+        };
+
+        children.push((key, value));
+    }
+}
+
+/// Parses a whole KeyValues document, the result is a section holding the top level keys
+pub fn parse(input: &str) -> anyhow::Result<KeyValues> {
+    let mut tokenizer = Tokenizer {
+        chars: input.trim_start_matches('\u{feff}').chars().peekable(),
+    };
+
+    Ok(KeyValues::Section(parse_section(&mut tokenizer, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(s: &str) -> KeyValues {
+        KeyValues::Value(s.to_string())
+    }
+
+    #[test]
+    fn nested_sections() {
+        let document = parse(r#""de_test" { "pos_x" "-2476" "verticalsections" { "lower" { "AltitudeMin" "-1000" } } }"#).unwrap();
+        let map = document.get("de_test").unwrap();
+
+        assert_eq!(map.get("pos_x"), Some(&value("-2476")));
+        assert_eq!(map.get_f32("pos_x"), Some(-2476.0));
+
+        let lower = map.get("VerticalSections").unwrap().get("lower").unwrap();
+        assert_eq!(lower.get_f32("altitudemin"), Some(-1000.0));
+    }
+
+    #[test]
+    fn comments_are_skipped() {
+        let document = parse("// header\n\"a\" \"1\" // trailing\n\"b\" \"http://x/y\"\n").unwrap();
+
+        assert_eq!(document.get("a"), Some(&value("1")));
+        assert_eq!(document.get("b"), Some(&value("http://x/y")));
+        assert_eq!(document.children().len(), 2);
+    }
+
+    #[test]
+    fn quoted_and_unquoted_tokens() {
+        let document = parse("key value\n\"quoted key\" \"with \\\"escape\\\"\"\nsection{inner 2}").unwrap();
+
+        assert_eq!(document.get("key"), Some(&value("value")));
+        assert_eq!(document.get("quoted key"), Some(&value("with \"escape\"")));
+        assert_eq!(document.get("section").unwrap().get("inner"), Some(&value("2")));
+    }
+
+    #[test]
+    fn lone_slash_is_a_value() {
+        let document = parse("a /").unwrap();
+        assert_eq!(document.get("a"), Some(&value("/")));
+    }
+
+    #[test]
+    fn unterminated_input_fails() {
+        assert!(parse("\"a\" \"unterminated").is_err());
+        assert!(parse("\"a\" { \"b\" \"1\"").is_err());
+        assert!(parse("\"a\"").is_err());
+        assert!(parse("}").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}, sync::Arc, time::Duration};
+
+use tokio::sync::RwLock;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{comms::ArcRwlockRadarData, structs::Vec3};
+
+mod transform;
+mod keyvalues;
+mod import;
+mod zones;
+
+pub use transform::{world_to_radar, RadarPos, RADAR_IMAGE_SIZE};
+pub use import::import;
+pub use zones::MapZone;
+
+/// How often the assets directory is scanned for new and changed maps
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+const JSON_DIR: &str = "json";
+const IMAGE_DIR: &str = "image";
+const IMAGE_SUFFIX: &str = "_radar_psd.png";
+
+pub type ArcMapRegistry = Arc<RwLock<MapRegistry>>;
+
+/// Radar calibration, same format the web client reads from `assets/json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapInfo {
+    pub pos_x: f32,
+    pub pos_y: f32,
+    pub scale: f32,
+
+    /// 0/1 flag from the overview file, positions don't depend on it
+    #[serde(default)]
+    pub rotate: i32,
+
+    #[serde(default)]
+    pub zoom: f32,
+
+    /// Vertical sections for maps with several floors, empty for single level maps
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<MapLayer>,
+
+    /// Named callout areas, the first zone containing a position wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<MapZone>,
+}
+
+/// A floor of a multi-level map, everything with `altitude_min <= z < altitude_max` is on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapLayer {
+    pub name: String,
+    pub altitude_min: f32,
+    pub altitude_max: f32,
+
+    /// Radar image of this floor in `assets/image`, the map's main image if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+impl MapInfo {
+    fn validate(&self) -> Result<(), String> {
+        if !self.pos_x.is_finite() || !self.pos_y.is_finite() {
+            return Err("pos_x and pos_y have to be finite".to_string());
+        }
+
+        if !self.scale.is_finite() || self.scale <= 0.0 {
+            return Err(format!("scale has to be positive, got {}", self.scale));
+        }
+
+        for zone in &self.zones {
+            if zone.points.len() < 3 {
+                return Err(format!("zone \"{}\" needs at least 3 points", zone.name));
+            }
+        }
+
+        for layer in &self.layers {
+            if layer.altitude_min >= layer.altitude_max {
+                return Err(format!("layer \"{}\" has altitude_min >= altitude_max", layer.name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Name of the callout zone containing `pos`
+    pub fn zone_at(&self, pos: &Vec3) -> Option<&str> {
+        self.zones
+            .iter()
+            .find(|zone| zone.contains(pos))
+            .map(|zone| zone.name.as_str())
+    }
+
+    /// Name of the layer at height `z`, None on single level maps
+    pub fn layer_at(&self, z: f32) -> Option<&str> {
+        self.layers
+            .iter()
+            .find(|layer| layer.altitude_min <= z && z < layer.altitude_max)
+            .map(|layer| layer.name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MapEntry {
+    pub name: String,
+
+    #[serde(rename = "hasJson")]
+    pub has_json: bool,
+
+    #[serde(rename = "hasImage")]
+    pub has_image: bool,
+
+    /// Only set if the json exists and is valid
+    pub info: Option<MapInfo>,
+
+    /// Why the json couldn't be used
+    pub error: Option<String>,
+
+    /// Layer images the json refers to that don't exist
+    #[serde(rename = "missingLayerImages")]
+    pub missing_layer_images: Vec<String>,
+}
+
+impl MapEntry {
+    pub fn is_usable(&self) -> bool {
+        self.info.is_some() && self.has_image
+    }
+}
+
+/// Every map found in the web assets directory
+#[derive(Debug)]
+pub struct MapRegistry {
+    assets: PathBuf,
+    maps: BTreeMap<String, MapEntry>,
+}
+
+impl MapRegistry {
+    pub fn new(web_path: &Path) -> MapRegistry {
+        let mut registry = MapRegistry {
+            assets: web_path.join("assets"),
+            maps: BTreeMap::new(),
+        };
+
+        registry.rescan();
+
+        let usable = registry.maps.values().filter(|entry| entry.is_usable()).count();
+        log::info!("Found {} usable maps in {}", usable, registry.assets.display());
+
+        for entry in registry.maps.values().filter(|entry| !entry.is_usable()) {
+            log::warn!("{}", describe_problem(entry));
+        }
+
+        registry
+    }
+
+    /// Reads the assets directory again, picks up new and changed maps
+    pub fn rescan(&mut self) {
+        self.maps = scan(&self.assets);
+    }
+
+    pub fn assets(&self) -> &Path {
+        &self.assets
+    }
+
+    /// Swaps in the result of a [`scan`] done without holding the lock
+    pub fn replace(&mut self, maps: BTreeMap<String, MapEntry>) {
+        self.maps = maps;
+    }
+
+    pub fn maps(&self) -> Vec<MapEntry> {
+        self.maps.values().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MapEntry> {
+        self.maps.get(name)
+    }
+
+    pub fn image_path(&self, name: &str) -> PathBuf {
+        self.assets.join(IMAGE_DIR).join(format!("{name}{IMAGE_SUFFIX}"))
+    }
+
+    /// Radar image of a floor, the main image if the layer has none or it isn't there
+    pub fn layer_image_path(&self, name: &str, layer: Option<&str>) -> PathBuf {
+        self.get(name)
+            .and_then(|entry| entry.info.as_ref())
+            .and_then(|info| info.layers.iter().find(|candidate| Some(candidate.name.as_str()) == layer))
+            .and_then(|layer| layer.image.as_ref())
+            .map(|image| self.assets.join(IMAGE_DIR).join(image))
+            .filter(|path| path.is_file())
+            .unwrap_or_else(|| self.image_path(name))
+    }
+
+    /// Logs a warning if the map can't be shown on the radar
+    pub fn check(&self, name: &str) {
+        match self.maps.get(name) {
+            Some(entry) if entry.is_usable() => {
+                for image in &entry.missing_layer_images {
+                    log::warn!("Map \"{}\" is missing layer image {}, falling back to the main image", name, image);
+                }
+            },
+            Some(entry) => log::warn!("{}", describe_problem(entry)),
+            None => log::warn!("No radar json or image for map \"{}\", the radar won't show it", name),
+        }
+    }
+}
+
+/// Reads every map json and radar image in the assets directory
+pub fn scan(assets: &Path) -> BTreeMap<String, MapEntry> {
+    let mut maps: BTreeMap<String, MapEntry> = BTreeMap::new();
+
+    for name in list_stems(&assets.join(JSON_DIR), ".json") {
+        let entry = maps.entry(name.clone()).or_insert_with(|| empty_entry(&name));
+        entry.has_json = true;
+
+        match load_info(&assets.join(JSON_DIR).join(format!("{name}.json"))) {
+            Ok(info) => entry.info = Some(info),
+            Err(e) => entry.error = Some(e),
+        }
+    }
+
+    for name in list_stems(&assets.join(IMAGE_DIR), IMAGE_SUFFIX) {
+        maps.entry(name.clone())
+            .or_insert_with(|| empty_entry(&name))
+            .has_image = true;
+    }
+
+    for entry in maps.values_mut() {
+        let Some(info) = &entry.info else {
+            continue;
+        };
+
+        entry.missing_layer_images = info.layers
+            .iter()
+            .filter_map(|layer| layer.image.as_ref())
+            .filter(|image| !assets.join(IMAGE_DIR).join(image).is_file())
+            .cloned()
+            .collect();
+    }
+
+    // Layer images share the suffix of the main images, they aren't maps of their own
+    let layer_images: Vec<String> = maps
+        .values()
+        .filter_map(|entry| entry.info.as_ref())
+        .flat_map(|info| info.layers.iter().filter_map(|layer| layer.image.as_ref()))
+        .filter_map(|image| image.strip_suffix(IMAGE_SUFFIX).map(str::to_string))
+        .collect();
+
+    maps.retain(|name, entry| entry.has_json || !layer_images.contains(name));
+
+    maps
+}
+
+fn empty_entry(name: &str) -> MapEntry {
+    MapEntry {
+        name: name.to_string(),
+        has_json: false,
+        has_image: false,
+        info: None,
+        error: None,
+        missing_layer_images: Vec::new(),
+    }
+}
+
+fn describe_problem(entry: &MapEntry) -> String {
+    if !entry.has_json {
+        format!("Map \"{}\" has a radar image but no json", entry.name)
+    } else if let Some(error) = &entry.error {
+        format!("Map \"{}\" has an invalid json: {}", entry.name, error)
+    } else {
+        format!("Map \"{}\" has a json but no radar image", entry.name)
+    }
+}
+
+/// File names in `dir` ending with `suffix`, with the suffix removed
+fn list_stems(dir: &Path, suffix: &str) -> Vec<String> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        log::debug!("Couldn't read {}", dir.display());
+        return Vec::new();
+    };
+
+    read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|file_name| file_name.strip_suffix(suffix).map(str::to_string))
+        .collect()
+}
+
+fn load_info(path: &Path) -> Result<MapInfo, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let info: MapInfo = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    info.validate()?;
+
+    Ok(info)
+}
+
+/// Rescans the assets every few seconds and checks the current map whenever the game changes maps
+pub async fn watch(registry: ArcMapRegistry, radar_data: ArcRwlockRadarData) {
+    let mut last_map = String::new();
+
+    loop {
+        tokio::time::sleep(RESCAN_INTERVAL).await;
+
+        // Directory and file reads block, keep them off the runtime and outside the lock
+        let assets = registry.read().await.assets().to_path_buf();
+        match tokio::task::spawn_blocking(move || scan(&assets)).await {
+            Ok(maps) => registry.write().await.replace(maps),
+            Err(e) => log::warn!("Map scan failed: {}", e),
+        }
+
+        let map = radar_data.read().await.get_map_name().to_string();
+        if map == last_map {
+            continue;
+        }
+
+        if !map.is_empty() {
+            registry.read().await.check(&map);
+        }
+
+        last_map = map;
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Serialize, Deserialize};
+
+use crate::structs::Vec3;
+
+use super::MapInfo;
+
+/// Radar images are square and this many pixels wide
+pub const RADAR_IMAGE_SIZE: f32 = 1024.0;
+
+/// Pixel position on the radar image, origin in the top left corner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RadarPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Projects a world position onto the radar image.
+/// `rotate` is a flag some overviews set, the web client ignores it and so do we,
+/// positions have to land where `mapCoordinates` in script.js draws them.
+pub fn world_to_radar(info: &MapInfo, pos: &Vec3) -> RadarPos {
+    RadarPos {
+        x: (pos.x - info.pos_x) / info.scale,
+        y: (info.pos_y - pos.y) / info.scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_info(pos_x: f32, pos_y: f32, scale: f32, rotate: i32) -> MapInfo {
+        MapInfo {
+            pos_x,
+            pos_y,
+            scale,
+            rotate,
+            zoom: 0.0,
+            layers: Vec::new(),
+            zones: Vec::new(),
+        }
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.01, "{a} != {b}");
+    }
+
+    #[test]
+    fn origin_maps_to_top_left() {
+        let info = map_info(-2476.0, 3239.0, 4.4, 0);
+        let radar = world_to_radar(&info, &Vec3 { x: -2476.0, y: 3239.0, z: 0.0 });
+
+        assert_close(radar.x, 0.0);
+        assert_close(radar.y, 0.0);
+    }
+
+    #[test]
+    fn y_axis_is_flipped() {
+        let info = map_info(0.0, 0.0, 2.0, 0);
+        let radar = world_to_radar(&info, &Vec3 { x: 200.0, y: -100.0, z: 0.0 });
+
+        assert_close(radar.x, 100.0);
+        assert_close(radar.y, 50.0);
+    }
+
+    #[test]
+    fn matches_web_client() {
+        // de_nuke calibration, pixel values taken from mapCoordinates in script.js
+        let info = map_info(-3453.0, 2887.0, 7.0, 0);
+        let radar = world_to_radar(&info, &Vec3 { x: 120.5, y: -1034.0, z: -400.0 });
+
+        assert_close(radar.x, 510.5);
+        assert_close(radar.y, 560.14);
+    }
+
+    #[test]
+    fn rotate_flag_matches_web_client() {
+        // de_dust2 ships with "rotate": 1, script.js draws it unrotated
+        let info = map_info(-2476.0, 3239.0, 4.4, 1);
+        let radar = world_to_radar(&info, &Vec3 { x: 1200.0, y: -800.0, z: 0.0 });
+
+        assert_close(radar.x, 835.45);
+        assert_close(radar.y, 917.95);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Serialize, Deserialize};
+
+use crate::structs::Vec3;
+
+/// A named callout area like "Long A" or "CT Spawn", as a polygon in world coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapZone {
+    pub name: String,
+
+    /// Corners as `[x, y]` world coordinates, in order
+    pub points: Vec<[f32; 2]>,
+
+    /// Height bounds, only needed where zones overlap on multi-level maps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z_min: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z_max: Option<f32>,
+}
+
+impl MapZone {
+    pub fn contains(&self, pos: &Vec3) -> bool {
+        if self.z_min.is_some_and(|z_min| pos.z < z_min) || self.z_max.is_some_and(|z_max| pos.z >= z_max) {
+            return false;
+        }
+
+        point_in_polygon(pos.x, pos.y, &self.points)
+    }
+}
+
+/// Even-odd ray casting test. Points on the lower x and y edges count as inside and points on the
+/// upper ones as outside, so two zones sharing an edge never both claim a position on it.
+fn point_in_polygon(x: f32, y: f32, points: &[[f32; 2]]) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut j = points.len() - 1;
+
+    for i in 0..points.len() {
+        let [xi, yi] = points[i];
+        let [xj, yj] = points[j];
+
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: [[f32; 2]; 4] = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+
+    fn zone(points: &[[f32; 2]], z_min: Option<f32>, z_max: Option<f32>) -> MapZone {
+        MapZone { name: "zone".to_string(), points: points.to_vec(), z_min, z_max }
+    }
+
+    #[test]
+    fn inside_and_outside() {
+        assert!(point_in_polygon(5.0, 5.0, &SQUARE));
+        assert!(!point_in_polygon(15.0, 5.0, &SQUARE));
+        assert!(!point_in_polygon(-5.0, 5.0, &SQUARE));
+        assert!(!point_in_polygon(5.0, -0.1, &SQUARE));
+    }
+
+    #[test]
+    fn concave_polygon() {
+        // An L shape, the missing top right quarter is outside
+        let l = [[0.0, 0.0], [10.0, 0.0], [10.0, 5.0], [5.0, 5.0], [5.0, 10.0], [0.0, 10.0]];
+
+        assert!(point_in_polygon(2.0, 8.0, &l));
+        assert!(point_in_polygon(8.0, 2.0, &l));
+        assert!(!point_in_polygon(8.0, 8.0, &l));
+    }
+
+    #[test]
+    fn edges_and_corners_are_half_open() {
+        assert!(point_in_polygon(0.0, 5.0, &SQUARE));
+        assert!(point_in_polygon(5.0, 0.0, &SQUARE));
+        assert!(point_in_polygon(0.0, 0.0, &SQUARE));
+
+        assert!(!point_in_polygon(10.0, 5.0, &SQUARE));
+        assert!(!point_in_polygon(5.0, 10.0, &SQUARE));
+        assert!(!point_in_polygon(10.0, 10.0, &SQUARE));
+    }
+
+    #[test]
+    fn shared_edge_belongs_to_one_zone() {
+        let right = [[10.0, 0.0], [20.0, 0.0], [20.0, 10.0], [10.0, 10.0]];
+
+        for y in [0.0, 2.5, 5.0, 9.9] {
+            assert!(point_in_polygon(10.0, y, &SQUARE) != point_in_polygon(10.0, y, &right));
+        }
+    }
+
+    #[test]
+    fn degenerate_polygons_contain_nothing() {
+        assert!(!point_in_polygon(0.0, 0.0, &[]));
+        assert!(!point_in_polygon(0.0, 0.0, &[[0.0, 0.0], [10.0, 10.0]]));
+    }
+
+    #[test]
+    fn height_bounds() {
+        let zone = zone(&SQUARE, Some(-100.0), Some(100.0));
+
+        assert!(zone.contains(&Vec3 { x: 5.0, y: 5.0, z: -100.0 }));
+        assert!(zone.contains(&Vec3 { x: 5.0, y: 5.0, z: 99.0 }));
+        assert!(!zone.contains(&Vec3 { x: 5.0, y: 5.0, z: 100.0 }));
+        assert!(!zone.contains(&Vec3 { x: 5.0, y: 5.0, z: -101.0 }));
+    }
+
+    #[test]
+    fn shipped_zones_are_valid() {
+        let info: crate::maps::MapInfo = serde_json::from_str(include_str!("../../webradar/assets/json/de_mirage.json")).unwrap();
+
+        assert!(info.validate().is_ok());
+        assert_eq!(info.zone_at(&Vec3 { x: -300.0, y: -2100.0, z: -160.0 }), Some("A Site"));
+        assert_eq!(info.zone_at(&Vec3 { x: -2100.0, y: 350.0, z: -160.0 }), Some("B Site"));
+        assert_eq!(info.zone_at(&Vec3 { x: 1800.0, y: 1500.0, z: 0.0 }), None);
+    }
+}
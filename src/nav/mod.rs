@@ -0,0 +1,197 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use serde::{Serialize, Deserialize};
+use tokio::sync::RwLock;
+
+use crate::structs::Vec3;
+
+mod parser;
+mod pathfinding;
+
+pub use pathfinding::{NavPath, RUN_SPEED};
+
+/// Max distance a position may be above an area's floor and still count as standing on it
+const MAX_AREA_HEIGHT: f32 = 80.0;
+
+pub type ArcNavStore = Arc<RwLock<NavStore>>;
+
+/// Walkable convex polygon of the navigation mesh
+#[derive(Debug, Clone)]
+pub struct NavArea {
+    pub id: u32,
+    pub corners: Vec<Vec3>,
+
+    /// Ids of the areas this one leads to
+    pub connections: Vec<u32>,
+
+    pub center: Vec3,
+}
+
+impl NavArea {
+    pub fn new(id: u32, corners: Vec<Vec3>, connections: Vec<u32>) -> NavArea {
+        let mut center = Vec3::default();
+        if !corners.is_empty() {
+            for corner in &corners {
+                center = center + *corner;
+            }
+            center = center * (1.0 / corners.len() as f32);
+        }
+
+        NavArea { id, corners, connections, center }
+    }
+
+    /// Checks if the position is inside the area seen from above
+    fn contains_2d(&self, pos: &Vec3) -> bool {
+        let mut inside = false;
+        let mut j = self.corners.len().wrapping_sub(1);
+
+        for (i, a) in self.corners.iter().enumerate() {
+            let b = &self.corners[j];
+            if (a.y > pos.y) != (b.y > pos.y) && pos.x < (b.x - a.x) * (pos.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+            j = i;
+        }
+
+        inside
+    }
+
+    /// Distance from the center to the farthest corner
+    pub fn radius(&self) -> f32 {
+        self.corners
+            .iter()
+            .map(|corner| corner.distance(&self.center))
+            .fold(0.0, f32::max)
+    }
+
+    /// Height of the area floor, corners are usually close enough that the average is fine
+    fn floor(&self) -> f32 {
+        self.center.z
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NavMesh {
+    pub version: u32,
+    pub areas: Vec<NavArea>,
+
+    /// Area id to index into `areas`
+    index: HashMap<u32, usize>,
+}
+
+impl NavMesh {
+    pub fn new(version: u32, areas: Vec<NavArea>) -> NavMesh {
+        let index = areas
+            .iter()
+            .enumerate()
+            .map(|(idx, area)| (area.id, idx))
+            .collect();
+
+        NavMesh { version, areas, index }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<NavMesh> {
+        parser::parse(&std::fs::read(path)?)
+    }
+
+    /// Area the position stands on, falls back to the area with the closest center
+    pub fn area_at(&self, pos: &Vec3) -> Option<&NavArea> {
+        self.areas
+            .iter()
+            .filter(|area| area.contains_2d(pos))
+            .filter(|area| pos.z >= area.floor() - MAX_AREA_HEIGHT && pos.z <= area.floor() + MAX_AREA_HEIGHT)
+            .min_by(|a, b| (pos.z - a.floor()).abs().total_cmp(&(pos.z - b.floor()).abs()))
+            .or_else(|| {
+                self.areas
+                    .iter()
+                    .min_by(|a, b| a.center.distance(pos).total_cmp(&b.center.distance(pos)))
+            })
+    }
+
+    /// Shortest walking path between two positions, None if they aren't connected
+    pub fn path(&self, from: &Vec3, to: &Vec3) -> Option<NavPath> {
+        pathfinding::find_path(self, from, to)
+    }
+
+    /// Areas within `budget` units of walking, closest first
+    pub fn reachable(&self, from: &Vec3, budget: f32) -> Vec<(&NavArea, f32)> {
+        pathfinding::reachable(self, from, budget)
+            .into_iter()
+            .map(|(idx, distance)| (&self.areas[idx], distance))
+            .collect()
+    }
+}
+
+/// Nav meshes loaded from `<nav_dir>/<map>.nav` on first use, see [`get`]
+pub struct NavStore {
+    nav_dir: PathBuf,
+
+    /// None if the map has no usable nav file, so we don't retry every request
+    meshes: HashMap<String, Option<Arc<NavMesh>>>,
+}
+
+impl NavStore {
+    pub fn new(nav_dir: &Path) -> NavStore {
+        NavStore {
+            nav_dir: nav_dir.to_path_buf(),
+            meshes: HashMap::new(),
+        }
+    }
+}
+
+/// Map names end up in a file path, so only plain names like `de_mirage` are accepted
+fn is_valid_map_name(map: &str) -> bool {
+    !map.is_empty() && map.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'_')
+}
+
+/// Nav mesh of the map, parsed off the runtime on first use and cached after that.
+/// Callers should only pass maps they know exist, every name gets a cache entry.
+pub async fn get(store: &ArcNavStore, map: &str) -> Option<Arc<NavMesh>> {
+    if !is_valid_map_name(map) {
+        log::warn!("Not loading a nav mesh for invalid map name {:?}", map);
+        return None;
+    }
+
+    let path = {
+        let store = store.read().await;
+        if let Some(mesh) = store.meshes.get(map) {
+            return mesh.clone();
+        }
+
+        store.nav_dir.join(format!("{map}.nav"))
+    };
+
+    let loaded = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || NavMesh::load(&path)).await
+    };
+
+    let mesh = match loaded {
+        Ok(Ok(mesh)) => {
+            log::info!("Loaded {} nav areas for {} (version {})", mesh.areas.len(), map, mesh.version);
+            Some(Arc::new(mesh))
+        },
+        Ok(Err(e)) => {
+            log::warn!("No usable nav mesh for {} at {}: {}", map, path.display(), e);
+            None
+        },
+        Err(e) => {
+            log::error!("Nav mesh loading for {} panicked: {}", map, e);
+            None
+        },
+    };
+
+    store.write().await.meshes.insert(map.to_string(), mesh.clone());
+    mesh
+}
+
+/// Query of the `/api/path` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathQuery {
+    pub from: Vec3,
+    pub to: Vec3,
+
+    /// Defaults to the map currently played
+    #[serde(default)]
+    pub map: Option<String>,
+}
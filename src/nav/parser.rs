@@ -0,0 +1,325 @@
+use crate::structs::Vec3;
+
+use super::{NavArea, NavMesh};
+
+const NAV_MAGIC: u32 = 0xFEEDFACE;
+const MIN_VERSION: u32 = 30;
+const MAX_VERSION: u32 = 36;
+
+/// Id, attributes, hull, corners, the unknown float and the legacy data with every list empty, no area can be shorter
+const AREA_MIN_SIZE: usize = 4 + 8 + 1 + 4 + 4 + 1 + 4 + 2 * 4 + 2 * 4 + 4 + 4 + 1;
+
+/// Little endian cursor over the raw file
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self.offset + len;
+        if end > self.data.len() {
+            return Err(anyhow::anyhow!("unexpected end of file at offset {:#x}", self.offset));
+        }
+
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Capacity for `count` records of at least `record_size` bytes, bounded by what's left so a bad count can't allocate gigabytes
+    fn capacity(&self, count: u32, record_size: usize) -> usize {
+        (count as usize).min((self.data.len() - self.offset) / record_size)
+    }
+
+    fn skip(&mut self, len: usize) -> anyhow::Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn f32(&mut self) -> anyhow::Result<f32> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn vec3(&mut self) -> anyhow::Result<Vec3> {
+        Ok(Vec3 {
+            x: self.f32()?,
+            y: self.f32()?,
+            z: self.f32()?,
+        })
+    }
+}
+
+/// Parses a CS2 `.nav` file, versions 30 to 36.
+/// Only areas and their connections are kept, everything after the area list is ignored.
+pub fn parse(data: &[u8]) -> anyhow::Result<NavMesh> {
+    let mut reader = Reader { data, offset: 0 };
+
+    let magic = reader.u32()?;
+    if magic != NAV_MAGIC {
+        return Err(anyhow::anyhow!("not a nav file, magic is {:#x}", magic));
+    }
+
+    let version = reader.u32()?;
+    if !(MIN_VERSION..=MAX_VERSION).contains(&version) {
+        return Err(anyhow::anyhow!("unsupported nav version {}", version));
+    }
+
+    let _sub_version = reader.u32()?;
+    let _is_analyzed = reader.u32()?;
+
+    // Since version 31 areas reference shared polygons instead of storing their own corners
+    let polygons = if version >= 31 {
+        read_polygons(&mut reader, version)?
+    } else {
+        Vec::new()
+    };
+
+    if version >= 32 {
+        reader.skip(4)?;
+    }
+
+    if version >= 35 {
+        reader.skip(4)?;
+    }
+
+    let area_count = reader.u32()?;
+    let mut areas = Vec::with_capacity(reader.capacity(area_count, AREA_MIN_SIZE));
+
+    for _ in 0..area_count {
+        areas.push(read_area(&mut reader, version, &polygons)?);
+    }
+
+    Ok(NavMesh::new(version, areas))
+}
+
+fn read_polygons(reader: &mut Reader, version: u32) -> anyhow::Result<Vec<Vec<Vec3>>> {
+    let corner_count = reader.u32()?;
+    let mut corners = Vec::with_capacity(reader.capacity(corner_count, 12));
+    for _ in 0..corner_count {
+        corners.push(reader.vec3()?);
+    }
+
+    let polygon_count = reader.u32()?;
+    let mut polygons = Vec::with_capacity(reader.capacity(polygon_count, 1));
+
+    for _ in 0..polygon_count {
+        let count = reader.u8()?;
+        let mut polygon = Vec::with_capacity(reader.capacity(count as u32, 4));
+
+        for _ in 0..count {
+            let index = reader.u32()? as usize;
+            let corner = corners
+                .get(index)
+                .ok_or_else(|| anyhow::anyhow!("polygon references corner {} of {}", index, corners.len()))?;
+
+            polygon.push(*corner);
+        }
+
+        if version >= 35 {
+            reader.skip(4)?;
+        }
+
+        polygons.push(polygon);
+    }
+
+    Ok(polygons)
+}
+
+fn read_area(reader: &mut Reader, version: u32, polygons: &[Vec<Vec3>]) -> anyhow::Result<NavArea> {
+    let id = reader.u32()?;
+    let _dynamic_attributes = reader.take(8)?;
+    let _hull_index = reader.u8()?;
+
+    let corners = if version >= 31 {
+        let index = reader.u32()? as usize;
+        polygons
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("area {} references polygon {} of {}", id, index, polygons.len()))?
+    } else {
+        let count = reader.u32()?;
+        let mut corners = Vec::with_capacity(reader.capacity(count, 12));
+        for _ in 0..count {
+            corners.push(reader.vec3()?);
+        }
+        corners
+    };
+
+    let _almost_always_zero = reader.f32()?;
+
+    // One list of connections per edge
+    let mut connections = Vec::new();
+    for _ in 0..corners.len() {
+        let count = reader.u32()?;
+        for _ in 0..count {
+            connections.push(reader.u32()?);
+            let _edge = reader.u32()?;
+        }
+    }
+
+    skip_legacy_data(reader)?;
+
+    Ok(NavArea::new(id, corners, connections))
+}
+
+/// Hiding spots, encounter paths, ladders, occupy times and visibility, none of which we need
+fn skip_legacy_data(reader: &mut Reader) -> anyhow::Result<()> {
+    // Hiding spots: id, position, flags
+    let hiding_spot_count = reader.u8()?;
+    reader.skip(hiding_spot_count as usize * (4 + 12 + 1))?;
+
+    // Encounter paths: from area and direction, to area and direction, then (spot id, t) pairs
+    let encounter_count = reader.u32()?;
+    for _ in 0..encounter_count {
+        reader.skip(4 + 1 + 4 + 1)?;
+        let spot_count = reader.u8()?;
+        reader.skip(spot_count as usize * (4 + 1))?;
+    }
+
+    // Ladders above and below
+    for _ in 0..2 {
+        let ladder_count = reader.u32()?;
+        reader.skip(ladder_count as usize * 4)?;
+    }
+
+    // Earliest occupy time per team
+    reader.skip(2 * 4)?;
+
+    // Visible areas: id and flags
+    let visible_count = reader.u32()?;
+    reader.skip(visible_count as usize * (4 + 1))?;
+
+    // Area this one inherits its visibility from
+    reader.skip(4)?;
+
+    // Unknown per area records
+    let unknown_count = reader.u8()?;
+    reader.skip(unknown_count as usize * 14)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three 100x100 squares in a row along x, each connected to its neighbours
+    fn three_areas_v35() -> Vec<u8> {
+        fn u32(data: &mut Vec<u8>, value: u32) {
+            data.extend(value.to_le_bytes());
+        }
+
+        let mut data = Vec::new();
+
+        u32(&mut data, NAV_MAGIC);
+        u32(&mut data, 35);
+        u32(&mut data, 0);
+        u32(&mut data, 1);
+
+        // Corners along y = 0 then y = 100
+        u32(&mut data, 8);
+        for y in [0.0f32, 100.0] {
+            for x in [0.0f32, 100.0, 200.0, 300.0] {
+                for value in [x, y, 0.0] {
+                    data.extend(value.to_le_bytes());
+                }
+            }
+        }
+
+        u32(&mut data, 3);
+        for i in 0..3 {
+            data.push(4);
+            for corner in [i, i + 1, i + 5, i + 4] {
+                u32(&mut data, corner);
+            }
+            u32(&mut data, 0);
+        }
+
+        // Unknown header fields of version 32 and 35
+        u32(&mut data, 0);
+        u32(&mut data, 0);
+
+        u32(&mut data, 3);
+        for i in 0..3u32 {
+            u32(&mut data, i + 1);
+            data.extend([0; 8]);
+            data.push(0);
+            u32(&mut data, i);
+            data.extend(0.0f32.to_le_bytes());
+
+            // Edges in corner order: bottom, right, top, left
+            let right = (i < 2).then_some(i + 2);
+            let left = (i > 0).then_some(i);
+            for neighbour in [None, right, None, left] {
+                match neighbour {
+                    Some(id) => {
+                        u32(&mut data, 1);
+                        u32(&mut data, id);
+                        u32(&mut data, 0);
+                    },
+                    None => u32(&mut data, 0),
+                }
+            }
+
+            // Hiding spots, encounters, ladders, occupy times, visible areas, inherit, unknown
+            data.push(0);
+            u32(&mut data, 0);
+            u32(&mut data, 0);
+            u32(&mut data, 0);
+            data.extend([0; 8]);
+            u32(&mut data, 0);
+            u32(&mut data, 0);
+            data.push(0);
+        }
+
+        data
+    }
+
+    #[test]
+    fn parses_version_35() {
+        let mesh = parse(&three_areas_v35()).unwrap();
+
+        assert_eq!(mesh.version, 35);
+        assert_eq!(mesh.areas.len(), 3);
+        assert_eq!(mesh.areas[0].connections, vec![2]);
+        assert_eq!(mesh.areas[1].connections, vec![3, 1]);
+        assert_eq!(mesh.areas[2].corners.len(), 4);
+        assert_eq!(mesh.areas[2].center.x, 250.0);
+        assert_eq!(mesh.areas[2].center.y, 50.0);
+    }
+
+    #[test]
+    fn truncated_input_fails() {
+        let data = three_areas_v35();
+
+        for len in 0..data.len() {
+            assert!(parse(&data[..len]).is_err(), "parsed {} of {} bytes", len, data.len());
+        }
+    }
+
+    #[test]
+    fn garbage_input_fails() {
+        assert!(parse(b"definitely not a nav file").is_err());
+
+        // Valid header claiming billions of corners
+        let mut data = Vec::new();
+        for value in [NAV_MAGIC, 35, 0, 1, u32::MAX] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend([0xAB; 64]);
+        assert!(parse(&data).is_err());
+
+        // Unsupported version
+        let mut data = three_areas_v35();
+        data[4] = 20;
+        assert!(parse(&data).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{cmp::Ordering, collections::{BinaryHeap, HashMap}};
+
+use serde::{Serialize, Deserialize};
+
+use crate::structs::Vec3;
+
+use super::NavMesh;
+
+/// Running speed with a knife out, in units per second
+pub const RUN_SPEED: f32 = 250.0;
+
+/// Result of a path lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavPath {
+    /// Walking distance in units
+    pub distance: f32,
+
+    /// Seconds it takes at `RUN_SPEED`
+    pub time: f32,
+
+    /// Start, area centers along the way, end
+    pub points: Vec<Vec3>,
+}
+
+#[derive(PartialEq)]
+struct Node {
+    cost: f32,
+    idx: usize,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the BinaryHeap pops the cheapest node first
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A* over area centers, the straight line to the goal is the heuristic
+pub fn find_path(mesh: &NavMesh, from: &Vec3, to: &Vec3) -> Option<NavPath> {
+    let start = mesh.index[&mesh.area_at(from)?.id];
+    let goal = mesh.index[&mesh.area_at(to)?.id];
+    let goal_center = mesh.areas[goal].center;
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut best: HashMap<usize, f32> = HashMap::new();
+
+    best.insert(start, 0.0);
+    open.push(Node { cost: mesh.areas[start].center.distance(&goal_center), idx: start });
+
+    while let Some(Node { idx, .. }) = open.pop() {
+        if idx == goal {
+            break;
+        }
+
+        let area = &mesh.areas[idx];
+        let cost = best[&idx];
+
+        for next in area.connections.iter().filter_map(|id| mesh.index.get(id).copied()) {
+            let next_center = mesh.areas[next].center;
+            let next_cost = cost + area.center.distance(&next_center);
+
+            if best.get(&next).map_or(true, |&known| next_cost < known) {
+                best.insert(next, next_cost);
+                came_from.insert(next, idx);
+                open.push(Node { cost: next_cost + next_center.distance(&goal_center), idx: next });
+            }
+        }
+    }
+
+    if !best.contains_key(&goal) {
+        return None;
+    }
+
+    let mut areas = vec![goal];
+    while let Some(&previous) = came_from.get(areas.last()?) {
+        areas.push(previous);
+    }
+    areas.reverse();
+
+    let mut points = vec![*from];
+    points.extend(areas.iter().map(|&idx| mesh.areas[idx].center));
+    points.push(*to);
+
+    let distance: f32 = points
+        .windows(2)
+        .map(|segment| segment[0].distance(&segment[1]))
+        .sum();
+
+    Some(NavPath {
+        distance: distance.round(),
+        time: distance / RUN_SPEED,
+        points,
+    })
+}
+
+/// Dijkstra from the area at `from`, stops expanding once `budget` is used up
+pub fn reachable(mesh: &NavMesh, from: &Vec3, budget: f32) -> Vec<(usize, f32)> {
+    let Some(start) = mesh.area_at(from).map(|area| mesh.index[&area.id]) else {
+        return Vec::new();
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut best: HashMap<usize, f32> = HashMap::new();
+    let mut reached = Vec::new();
+
+    best.insert(start, 0.0);
+    open.push(Node { cost: 0.0, idx: start });
+
+    while let Some(Node { cost, idx }) = open.pop() {
+        // Stale heap entry, a shorter way was found already
+        if cost > best[&idx] {
+            continue;
+        }
+
+        reached.push((idx, cost));
+
+        let area = &mesh.areas[idx];
+        for next in area.connections.iter().filter_map(|id| mesh.index.get(id).copied()) {
+            let next_cost = cost + area.center.distance(&mesh.areas[next].center);
+
+            if next_cost <= budget && best.get(&next).map_or(true, |&known| next_cost < known) {
+                best.insert(next, next_cost);
+                open.push(Node { cost: next_cost, idx: next });
+            }
+        }
+    }
+
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nav::NavArea;
+
+    use super::*;
+
+    fn square(id: u32, x: f32, connections: Vec<u32>) -> NavArea {
+        let corners = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]
+            .map(|(dx, y)| Vec3 { x: x + dx, y, z: 0.0 })
+            .to_vec();
+
+        NavArea::new(id, corners, connections)
+    }
+
+    /// Three squares in a row, only connected to their neighbours
+    fn mesh() -> NavMesh {
+        NavMesh::new(35, vec![square(1, 0.0, vec![2]), square(2, 100.0, vec![1, 3]), square(3, 200.0, vec![2])])
+    }
+
+    #[test]
+    fn path_goes_through_every_area() {
+        let from = Vec3 { x: 10.0, y: 50.0, z: 0.0 };
+        let to = Vec3 { x: 290.0, y: 50.0, z: 0.0 };
+        let path = mesh().path(&from, &to).unwrap();
+
+        let xs: Vec<f32> = path.points.iter().map(|point| point.x).collect();
+        assert_eq!(xs, vec![10.0, 50.0, 150.0, 250.0, 290.0]);
+        assert_eq!(path.distance, 380.0);
+        assert_eq!(path.time, 380.0 / RUN_SPEED);
+    }
+
+    #[test]
+    fn disconnected_areas_have_no_path() {
+        let mesh = NavMesh::new(35, vec![square(1, 0.0, vec![]), square(2, 100.0, vec![])]);
+        let from = Vec3 { x: 10.0, y: 50.0, z: 0.0 };
+        let to = Vec3 { x: 190.0, y: 50.0, z: 0.0 };
+
+        assert!(mesh.path(&from, &to).is_none());
+    }
+
+    #[test]
+    fn reachable_stops_at_budget() {
+        let mesh = mesh();
+        let from = Vec3 { x: 10.0, y: 50.0, z: 0.0 };
+
+        let ids: Vec<u32> = mesh.reachable(&from, 150.0).iter().map(|(area, _)| area.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(mesh.reachable(&from, 200.0).len(), 3);
+    }
+}
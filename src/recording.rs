@@ -0,0 +1,142 @@
+use std::{fs::File, io::{BufRead, BufReader, BufWriter, Write}, path::{Path, PathBuf}, time::Duration};
+
+use crate::comms::{ArcRwlockRadarData, RadarData};
+
+/// How often the radar data is checked for a new tick
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Appends every new in-game frame to `path`, one JSON object per line
+pub async fn record(path: PathBuf, radar_data: ArcRwlockRadarData) {
+    let file = match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!("Failed to open recording {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    log::info!("Recording to {}", path.display());
+
+    let mut writer = BufWriter::new(file);
+    let mut last_tick = None;
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let line = {
+            let radar = radar_data.read().await;
+            if !radar.is_ingame() || last_tick == Some(radar.tick) {
+                continue;
+            }
+
+            last_tick = Some(radar.tick);
+            serde_json::to_string(&*radar)
+        };
+
+        let result = match line {
+            Ok(line) => writeln!(writer, "{}", line).and_then(|_| writer.flush()),
+            Err(e) => {
+                log::warn!("Failed to serialize frame for recording: {}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = result {
+            log::error!("Failed to write recording {}: {}", path.display(), e);
+            return;
+        }
+    }
+}
+
+/// Reads a recording frame by frame, lines that don't parse are skipped with a warning
+pub fn frames(path: &Path) -> anyhow::Result<impl Iterator<Item = RadarData>> {
+    let reader = BufReader::new(File::open(path)?);
+    let name = path.display().to_string();
+
+    Ok(reader
+        .lines()
+        .enumerate()
+        .filter_map(move |(idx, line)| {
+            let frame = line
+                .map_err(anyhow::Error::from)
+                .and_then(|line| Ok(serde_json::from_str::<RadarData>(&line)?));
+
+            match frame {
+                Ok(frame) => Some(frame),
+                Err(e) => {
+                    log::warn!("Skipping line {} of {}: {}", idx + 1, name, e);
+                    None
+                }
+            }
+        }))
+}
+
+/// Numbers the matches in a stream of frames, starting at 1, and drops frames from outside a game.
+/// Recordings are appended to, so a new session starts wherever the game clock jumps back or the map changes.
+pub fn sessions(frames: impl Iterator<Item = RadarData>) -> impl Iterator<Item = (usize, RadarData)> {
+    let mut session = 0;
+    let mut last_time = f32::MIN;
+    let mut last_map = String::new();
+
+    frames.filter(RadarData::is_ingame).map(move |frame| {
+        if frame.cur_time < last_time || frame.get_map_name() != last_map {
+            session += 1;
+            last_map = frame.get_map_name().to_string();
+        }
+
+        last_time = frame.cur_time;
+        (session, frame)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{comms::{BombData, EntityData, GameModeData, MatchData}, structs::Vec3};
+
+    use super::*;
+
+    fn frame(map: &str, cur_time: f32) -> RadarData {
+        let mut frame = RadarData::new(true, map.to_string(), MatchData::default(), GameModeData::default(), Vec::new(), Vec::new(), 64, false, false, 0.0, false, false, 0.0, 0.0);
+        frame.cur_time = cur_time;
+        frame
+    }
+
+    #[test]
+    fn sessions_split_on_clock_reset_and_map_change() {
+        let frames = vec![
+            frame("de_mirage", 10.0),
+            frame("de_mirage", 11.0),
+            frame("de_mirage", 5.0),
+            frame("de_nuke", 6.0),
+            RadarData::empty(64),
+            frame("de_nuke", 7.0),
+        ];
+
+        let numbers: Vec<usize> = sessions(frames.into_iter()).map(|(session, _)| session).collect();
+        assert_eq!(numbers, vec![1, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn recorded_lines_read_back() {
+        let path = std::env::temp_dir().join(format!("recording-test-{}.jsonl", std::process::id()));
+
+        let bomb = EntityData::Bomb(BombData::new(Vec3 { x: 1.0, y: 2.0, z: 3.0 }, true));
+        let mut frame = RadarData::new(true, "de_mirage".to_string(), MatchData::default(), GameModeData::default(), vec![bomb], Vec::new(), 64, true, false, 30.0, false, false, 10.0, 0.0);
+        frame.tick = 1234;
+        frame.cur_time = 56.5;
+
+        // Same serialization as `record`, plus a broken line that has to be skipped
+        let line = serde_json::to_string(&frame).unwrap();
+        std::fs::write(&path, format!("{line}\n{{\"broken\"\n{line}\n")).unwrap();
+
+        let read: Vec<RadarData> = frames(&path).unwrap().collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.len(), 2);
+        assert!(read[0].is_ingame());
+        assert_eq!(read[0].get_map_name(), "de_mirage");
+        assert_eq!(read[0].tick, 1234);
+        assert_eq!(read[0].cur_time, 56.5);
+        assert!(matches!(read[0].get_entities()[..], [EntityData::Bomb(ref bomb)] if bomb.is_planted()));
+    }
+}
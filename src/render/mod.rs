@@ -0,0 +1,380 @@
+use std::{collections::{BTreeMap, HashMap}, fs::File, io::Cursor, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::SystemTime};
+
+use image::{codecs::gif::{GifEncoder, Repeat}, imageops::{self, FilterType}, Delay, Frame, ImageFormat, Rgba, RgbaImage};
+
+use crate::{comms::{EntityData, RadarData}, enums::{MarkerKind, PlayerType}, maps::{self, MapInfo, MapRegistry, RADAR_IMAGE_SIZE}, recording, structs::Vec3};
+
+mod font;
+
+pub const DEFAULT_SIZE: u32 = 512;
+pub const MIN_SIZE: u32 = 128;
+pub const MAX_SIZE: u32 = 2048;
+
+// Same colors as the web client
+const LOCAL_COLOR: Rgba<u8> = Rgba([0x10, 0x98, 0x56, 255]);
+const TEAM_COLOR: Rgba<u8> = Rgba([0x68, 0xa3, 0xe5, 255]);
+const ENEMY_COLOR: Rgba<u8> = Rgba([0xec, 0x04, 0x0b, 255]);
+const BOMB_COLOR: Rgba<u8> = Rgba([0xed, 0xa3, 0x38, 255]);
+const HOSTAGE_COLOR: Rgba<u8> = Rgba([0xf2, 0xe6, 0xc9, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([0xd1, 0xd1, 0xd1, 255]);
+const OUTLINE_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// World units the view direction line points ahead
+const YAW_LINE_LENGTH: f32 = 120.0;
+
+/// Opacity of everything that isn't on the shown floor, same as the web client
+const OTHER_LAYER_ALPHA: f32 = 0.35;
+
+fn player_color(player_type: PlayerType) -> Rgba<u8> {
+    match player_type {
+        PlayerType::Local => LOCAL_COLOR,
+        PlayerType::Team => TEAM_COLOR,
+        PlayerType::Enemy => ENEMY_COLOR,
+        _ => TEXT_COLOR,
+    }
+}
+
+/// Floor the radar shows, the one of the local or observed player like in the web client
+pub fn current_layer<'a>(frame: &RadarData, info: &'a MapInfo) -> Option<&'a str> {
+    frame.get_entities()
+        .iter()
+        .find_map(|entity| match entity {
+            EntityData::Player(player) if player.player_type() == PlayerType::Local || player.is_observed() => Some(player.pos()),
+            _ => None,
+        })
+        .and_then(|pos| info.layer_at(pos.z))
+}
+
+/// Alpha blends `color` onto the pixel, ignores coordinates outside the image
+fn put_pixel(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>) {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return;
+    }
+
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    let alpha = color.0[3] as f32 / 255.0;
+
+    for (channel, color) in pixel.0.iter_mut().zip(color.0).take(3) {
+        *channel = (*channel as f32 * (1.0 - alpha) + color as f32 * alpha).round() as u8;
+    }
+
+    pixel.0[3] = pixel.0[3].max(color.0[3]);
+}
+
+fn fill_circle(image: &mut RgbaImage, x: f32, y: f32, radius: f32, color: Rgba<u8>) {
+    let r = radius.ceil() as i32;
+    let (cx, cy) = (x.round() as i32, y.round() as i32);
+
+    for dy in -r..=r {
+        for dx in -r..=r {
+            if ((dx * dx + dy * dy) as f32) <= radius * radius {
+                put_pixel(image, cx + dx, cy + dy, color);
+            }
+        }
+    }
+}
+
+fn draw_ring(image: &mut RgbaImage, x: f32, y: f32, radius: f32, width: f32, color: Rgba<u8>) {
+    let r = (radius + width).ceil() as i32;
+    let (cx, cy) = (x.round() as i32, y.round() as i32);
+
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            if distance >= radius && distance <= radius + width {
+                put_pixel(image, cx + dx, cy + dy, color);
+            }
+        }
+    }
+}
+
+fn draw_line(image: &mut RgbaImage, from: (f32, f32), to: (f32, f32), width: f32, color: Rgba<u8>) {
+    let length = (to.0 - from.0).hypot(to.1 - from.1);
+    let steps = length.ceil().max(1.0) as i32;
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        fill_circle(image, from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t, width / 2.0, color);
+    }
+}
+
+fn fill_square(image: &mut RgbaImage, x: f32, y: f32, half: f32, color: Rgba<u8>) {
+    let h = half.round() as i32;
+    let (cx, cy) = (x.round() as i32, y.round() as i32);
+
+    for dy in -h..=h {
+        for dx in -h..=h {
+            put_pixel(image, cx + dx, cy + dy, color);
+        }
+    }
+}
+
+/// Draws a frame the way the web client shows it without rotation or zoom: map, markers, bomb, hostages and players with names.
+/// `background` should be the image of the [`current_layer`], everything on other floors is dimmed.
+pub fn render_frame(frame: &RadarData, info: &MapInfo, background: &RgbaImage, size: u32) -> RgbaImage {
+    let mut image = imageops::resize(background, size, size, FilterType::Triangle);
+
+    let layer = current_layer(frame, info);
+    let dim = |pos: &Vec3, mut color: Rgba<u8>| {
+        if layer.is_some() && info.layer_at(pos.z) != layer {
+            color.0[3] = (color.0[3] as f32 * OTHER_LAYER_ALPHA).round() as u8;
+        }
+
+        color
+    };
+
+    let scale = size as f32 / RADAR_IMAGE_SIZE;
+    let unit = (size as f32 / DEFAULT_SIZE as f32).max(0.5);
+    let text_scale = (size / DEFAULT_SIZE).max(1);
+
+    let project = |pos: &Vec3| {
+        let radar = maps::world_to_radar(info, pos);
+        (radar.x * scale, radar.y * scale)
+    };
+
+    let entities = frame.get_entities();
+
+    for entity in entities {
+        match entity {
+            EntityData::Marker(marker) if marker.kind() == MarkerKind::Death => {
+                let (x, y) = project(&marker.pos());
+                let arm = 4.0 * unit;
+                let mut color = player_color(marker.player_type());
+                color.0[3] = 160;
+                let color = dim(&marker.pos(), color);
+
+                draw_line(&mut image, (x - arm, y - arm), (x + arm, y + arm), 2.0 * unit, color);
+                draw_line(&mut image, (x + arm, y - arm), (x - arm, y + arm), 2.0 * unit, color);
+            },
+            EntityData::Hostage(_) => {
+                let (x, y) = project(&entity.pos());
+                fill_circle(&mut image, x, y, 4.0 * unit + 1.0, dim(&entity.pos(), OUTLINE_COLOR));
+                fill_circle(&mut image, x, y, 4.0 * unit, dim(&entity.pos(), HOSTAGE_COLOR));
+            },
+            EntityData::Bomb(bomb) => {
+                let (x, y) = project(&entity.pos());
+                fill_square(&mut image, x, y, 5.0 * unit + 1.0, dim(&entity.pos(), OUTLINE_COLOR));
+                fill_square(&mut image, x, y, 5.0 * unit, dim(&entity.pos(), BOMB_COLOR));
+
+                if bomb.is_planted() {
+                    draw_ring(&mut image, x, y, 8.0 * unit, 2.0 * unit, dim(&entity.pos(), ENEMY_COLOR));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let radius = 6.0 * unit;
+
+    for entity in entities {
+        let EntityData::Player(player) = entity else {
+            continue;
+        };
+
+        let pos = player.pos();
+        let (x, y) = project(&pos);
+        let color = dim(&pos, player_color(player.player_type()));
+
+        // The line points wherever the player is looking, projected like the position so the y flip is handled
+        let (sin, cos) = player.yaw().to_radians().sin_cos();
+        let ahead = project(&(pos + Vec3 { x: cos, y: sin, z: 0.0 } * YAW_LINE_LENGTH));
+        let (dx, dy) = (ahead.0 - x, ahead.1 - y);
+        let length = dx.hypot(dy).max(f32::EPSILON);
+        let tip = (x + dx / length * radius * 2.0, y + dy / length * radius * 2.0);
+
+        draw_line(&mut image, (x, y), tip, 2.0 * unit, color);
+        fill_circle(&mut image, x, y, radius + 1.0, dim(&pos, OUTLINE_COLOR));
+        fill_circle(&mut image, x, y, radius, color);
+
+        if player.has_bomb() {
+            draw_ring(&mut image, x, y, radius + 1.0, 2.0 * unit, dim(&pos, BOMB_COLOR));
+        }
+    }
+
+    // Names last so no circle covers them
+    for entity in entities {
+        let EntityData::Player(player) = entity else {
+            continue;
+        };
+
+        let (x, y) = project(&player.pos());
+        let top = (y + radius + 3.0 * unit) as i32;
+        font::draw_text(&mut image, x as i32, top, player.player_name(), dim(&player.pos(), TEXT_COLOR), text_scale);
+    }
+
+    image
+}
+
+/// Decoded radar images, so `/api/render.png` doesn't decode the same png on every request
+#[derive(Default)]
+pub struct BackgroundCache {
+    /// Image path to the modification time it was decoded at and the image
+    images: Mutex<HashMap<PathBuf, (SystemTime, Arc<RgbaImage>)>>,
+}
+
+impl BackgroundCache {
+    /// Decoded image at `path`, decoded again if the file changed since
+    pub fn get(&self, path: &Path) -> anyhow::Result<Arc<RgbaImage>> {
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        if let Some((decoded_at, image)) = self.images.lock().unwrap().get(path) {
+            if *decoded_at == modified {
+                return Ok(image.clone());
+            }
+        }
+
+        let image = Arc::new(image::open(path)?.to_rgba8());
+        self.images.lock().unwrap().insert(path.to_path_buf(), (modified, image.clone()));
+
+        Ok(image)
+    }
+}
+
+/// Renders a frame onto the radar image at `image_path`, which should be the one of the [`current_layer`], and encodes it as png
+pub fn render_png(frame: &RadarData, info: &MapInfo, image_path: &Path, backgrounds: &BackgroundCache, size: u32) -> anyhow::Result<Vec<u8>> {
+    let background = backgrounds.get(image_path)?;
+    let image = render_frame(frame, info, &background, size);
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    Ok(png)
+}
+
+/// Renders one round of a recording as an animated gif, at `fps` frames per second of game time.
+/// If the recording holds several matches with that round, `session` picks one of them, 1-based
+/// and numbered like `recording::sessions`. The recording is read twice so it never has to fit in memory.
+pub fn export_round(recording: &Path, round: i32, session: Option<usize>, web_path: &Path, size: u32, fps: u32, output: &Path) -> anyhow::Result<()> {
+    if output.extension().map_or(true, |ext| !ext.eq_ignore_ascii_case("gif")) {
+        return Err(anyhow::anyhow!("only .gif output is supported"));
+    }
+
+    // Session to the map it was played on, for every session that has the round
+    let mut candidates: BTreeMap<usize, String> = BTreeMap::new();
+    for (session, frame) in recording::sessions(recording::frames(recording)?) {
+        if frame.get_match_data().round == round {
+            candidates.entry(session).or_insert_with(|| frame.get_map_name().to_string());
+        }
+    }
+
+    let listed = || {
+        candidates
+            .iter()
+            .map(|(session, map)| format!("{session} ({map})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let session = match (candidates.len(), session) {
+        (0, _) => return Err(anyhow::anyhow!("round {} isn't in {}", round, recording.display())),
+        (1, None) => *candidates.keys().next().unwrap(),
+        (_, None) => {
+            return Err(anyhow::anyhow!("round {} was played in sessions {}, pick one with --session", round, listed()));
+        },
+        (_, Some(session)) if !candidates.contains_key(&session) => {
+            return Err(anyhow::anyhow!("session {} has no round {}, it's in sessions {}", session, round, listed()));
+        },
+        (_, Some(session)) => session,
+    };
+
+    let map = candidates[&session].clone();
+    let registry = MapRegistry::new(web_path);
+    let info = registry
+        .get(&map)
+        .and_then(|entry| entry.info.clone())
+        .ok_or_else(|| anyhow::anyhow!("no usable radar json for {}", map))?;
+
+    let backgrounds = BackgroundCache::default();
+
+    let interval = 1.0 / fps.max(1) as f32;
+    let delay = Delay::from_numer_denom_ms(1000, fps.max(1));
+
+    let mut encoder = GifEncoder::new_with_speed(File::create(output)?, 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let mut next_time = f32::MIN;
+    let mut rendered = 0;
+
+    let frames = recording::sessions(recording::frames(recording)?)
+        .filter(|(frame_session, frame)| *frame_session == session && frame.get_match_data().round == round)
+        .map(|(_, frame)| frame);
+
+    for frame in frames {
+        if frame.cur_time < next_time {
+            continue;
+        }
+
+        next_time = frame.cur_time + interval;
+
+        let background = backgrounds.get(&registry.layer_image_path(&map, current_layer(&frame, &info)))?;
+        let image = render_frame(&frame, &info, &background, size);
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+        rendered += 1;
+    }
+
+    println!("Rendered {} frames of round {} on {} to {}", rendered, round, map, output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{comms::{GameModeData, MatchData, PlayerData}, maps::MapLayer};
+
+    use super::*;
+
+    fn two_floors() -> MapInfo {
+        let layer = |name: &str, altitude_min, altitude_max| MapLayer { name: name.to_string(), altitude_min, altitude_max, image: None };
+        MapInfo {
+            pos_x: -1000.0,
+            pos_y: 1000.0,
+            scale: 2.0,
+            rotate: 0,
+            zoom: 0.0,
+            layers: vec![layer("lower", -10000.0, -500.0), layer("default", -500.0, 10000.0)],
+            zones: Vec::new(),
+        }
+    }
+
+    fn player(x: f32, z: f32, player_type: PlayerType, is_observed: bool) -> EntityData {
+        let pos = Vec3 { x, y: 0.0, z };
+        EntityData::Player(PlayerData::new(pos, 0.0, player_type, None, false, false, false, "a".to_string(), 0, 0, 100, is_observed))
+    }
+
+    fn frame(entities: Vec<EntityData>) -> RadarData {
+        RadarData::new(true, "de_nuke".to_string(), MatchData::default(), GameModeData::default(), entities, Vec::new(), 64, false, false, 0.0, false, false, 0.0, 0.0)
+    }
+
+    #[test]
+    fn current_layer_follows_local_or_observed_player() {
+        let info = two_floors();
+
+        let local = frame(vec![player(0.0, 0.0, PlayerType::Enemy, false), player(0.0, -700.0, PlayerType::Local, false)]);
+        assert_eq!(current_layer(&local, &info), Some("lower"));
+
+        let spectating = frame(vec![player(0.0, -700.0, PlayerType::Enemy, false), player(0.0, 0.0, PlayerType::Team, true)]);
+        assert_eq!(current_layer(&spectating, &info), Some("default"));
+
+        let nobody = frame(vec![player(0.0, -700.0, PlayerType::Enemy, false)]);
+        assert_eq!(current_layer(&nobody, &info), None);
+    }
+
+    #[test]
+    fn other_floors_are_dimmed() {
+        let info = two_floors();
+        let background = RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 255]));
+
+        // The same spot seen from both floors, the spectated player stands elsewhere
+        let center = |frame: &RadarData| {
+            let image = render_frame(frame, &info, &background, DEFAULT_SIZE);
+            let radar = maps::world_to_radar(&info, &Vec3::default());
+            let scale = DEFAULT_SIZE as f32 / RADAR_IMAGE_SIZE;
+            *image.get_pixel((radar.x * scale) as u32, (radar.y * scale) as u32)
+        };
+
+        let same_floor = center(&frame(vec![player(0.0, 0.0, PlayerType::Local, false)]));
+        let other_floor = center(&frame(vec![player(0.0, -700.0, PlayerType::Enemy, false), player(500.0, 0.0, PlayerType::Team, true)]));
+
+        assert_eq!(same_floor, LOCAL_COLOR);
+        assert!(other_floor.0[0] < ENEMY_COLOR.0[0] / 2);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use memflow::types::Address;
+
+use crate::structs::Vec3;
+
+/// How much we expect players to accelerate between ticks, in units/s²
+const ACCEL_NOISE: f32 = 800.0;
+/// Measurement noise of the read position, in units
+const POS_NOISE: f32 = 2.0;
+/// Measurement noise of the read velocity, in units/s
+const VEL_NOISE: f32 = 20.0;
+
+/// Jumps further than this are teleports (spawns, round restarts) and reset the filter
+const TELEPORT_DISTANCE: f32 = 500.0;
+/// Gaps longer than this reset the filter, the old state is meaningless by then
+const MAX_DT: f32 = 1.0;
+
+/// Constant velocity kalman filter for a single axis.
+/// Both position and velocity are measured, so the observation matrix is the identity.
+#[derive(Debug, Clone, Copy)]
+struct AxisFilter {
+    pos: f32,
+    vel: f32,
+    /// Covariance, row major
+    p: [f32; 4],
+}
+
+impl AxisFilter {
+    fn new(pos: f32, vel: f32) -> AxisFilter {
+        AxisFilter {
+            pos,
+            vel,
+            p: [POS_NOISE * POS_NOISE, 0.0, 0.0, VEL_NOISE * VEL_NOISE],
+        }
+    }
+
+    fn predict(&mut self, dt: f32) {
+        self.pos += self.vel * dt;
+
+        // P = F * P * F^T + Q
+        let [p00, p01, p10, p11] = self.p;
+        let q = ACCEL_NOISE * ACCEL_NOISE;
+        let dt2 = dt * dt;
+
+        self.p = [
+            p00 + dt * (p10 + p01) + dt2 * p11 + q * dt2 * dt2 / 4.0,
+            p01 + dt * p11 + q * dt2 * dt / 2.0,
+            p10 + dt * p11 + q * dt2 * dt / 2.0,
+            p11 + q * dt2,
+        ];
+    }
+
+    fn update(&mut self, pos: f32, vel: f32) {
+        let [p00, p01, p10, p11] = self.p;
+
+        // S = P + R
+        let s00 = p00 + POS_NOISE * POS_NOISE;
+        let s11 = p11 + VEL_NOISE * VEL_NOISE;
+        let det = s00 * s11 - p01 * p10;
+
+        if det.abs() < f32::EPSILON {
+            *self = AxisFilter::new(pos, vel);
+            return;
+        }
+
+        // K = P * S^-1
+        let k00 = (p00 * s11 - p01 * p10) / det;
+        let k01 = (p01 * s00 - p00 * p01) / det;
+        let k10 = (p10 * s11 - p11 * p10) / det;
+        let k11 = (p11 * s00 - p10 * p01) / det;
+
+        let pos_residual = pos - self.pos;
+        let vel_residual = vel - self.vel;
+
+        self.pos += k00 * pos_residual + k01 * vel_residual;
+        self.vel += k10 * pos_residual + k11 * vel_residual;
+
+        // P = (I - K) * P
+        self.p = [
+            (1.0 - k00) * p00 - k01 * p10,
+            (1.0 - k00) * p01 - k01 * p11,
+            -k10 * p00 + (1.0 - k11) * p10,
+            -k10 * p01 + (1.0 - k11) * p11,
+        ];
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PlayerFilter {
+    axes: [AxisFilter; 3],
+    last_time: f32,
+}
+
+impl PlayerFilter {
+    fn new(pos: Vec3, vel: Vec3, time: f32) -> PlayerFilter {
+        PlayerFilter {
+            axes: [
+                AxisFilter::new(pos.x, vel.x),
+                AxisFilter::new(pos.y, vel.y),
+                AxisFilter::new(pos.z, vel.z),
+            ],
+            last_time: time,
+        }
+    }
+
+    fn pos(&self) -> Vec3 {
+        Vec3 { x: self.axes[0].pos, y: self.axes[1].pos, z: self.axes[2].pos }
+    }
+
+    fn vel(&self) -> Vec3 {
+        Vec3 { x: self.axes[0].vel, y: self.axes[1].vel, z: self.axes[2].vel }
+    }
+}
+
+/// Smooths player positions over time and extrapolates them a short time into the future
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    filters: HashMap<Address, PlayerFilter>,
+    prediction: f32,
+}
+
+impl Smoother {
+    pub fn new(prediction_ms: u32) -> Smoother {
+        Smoother {
+            filters: HashMap::new(),
+            prediction: prediction_ms as f32 / 1000.0,
+        }
+    }
+
+    /// Feeds a new measurement taken at game time `time`, returns the filtered and the predicted position
+    pub fn update(&mut self, player: Address, pos: Vec3, vel: Vec3, time: f32) -> (Vec3, Vec3) {
+        let filter = self.filters
+            .entry(player)
+            .or_insert_with(|| PlayerFilter::new(pos, vel, time));
+
+        let dt = time - filter.last_time;
+
+        if dt < 0.0 || dt > MAX_DT || filter.pos().distance(&pos) > TELEPORT_DISTANCE {
+            *filter = PlayerFilter::new(pos, vel, time);
+        } else if dt > 0.0 {
+            let measured = [(pos.x, vel.x), (pos.y, vel.y), (pos.z, vel.z)];
+
+            for (axis, (pos, vel)) in filter.axes.iter_mut().zip(measured) {
+                axis.predict(dt);
+                axis.update(pos, vel);
+            }
+
+            filter.last_time = time;
+        }
+
+        let filtered = filter.pos();
+        (filtered, filtered + filter.vel() * self.prediction)
+    }
+
+    /// Drops the state of players that weren't updated this frame
+    pub fn retain(&mut self, players: &[Address]) {
+        self.filters.retain(|player, _| players.contains(player));
+    }
+}
@@ -1,3 +1,5 @@
 mod vec3;
+mod player_flags;
 
-pub use vec3::Vec3;
\ No newline at end of file
+pub use vec3::Vec3;
+pub use player_flags::PlayerFlags;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use serde::{Serialize, Deserialize};
+
+/// Bitfield of what a player is currently doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerFlags(pub u16);
+
+impl PlayerFlags {
+    pub const FLASHED: u16 = 1 << 0;
+    pub const DEFUSING: u16 = 1 << 1;
+    pub const PLANTING: u16 = 1 << 2;
+    pub const RELOADING: u16 = 1 << 3;
+    pub const CROUCHING: u16 = 1 << 4;
+    pub const WALKING: u16 = 1 << 5;
+    pub const RUNNING: u16 = 1 << 6;
+    pub const ON_GROUND: u16 = 1 << 7;
+    pub const ON_LADDER: u16 = 1 << 8;
+
+    pub fn set(&mut self, flag: u16, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+
+    pub fn contains(&self, flag: u16) -> bool {
+        self.0 & flag != 0
+    }
+}
\ No newline at end of file
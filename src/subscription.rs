@@ -0,0 +1,356 @@
+use std::{collections::{HashMap, HashSet}, path::Path};
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+/// Fields of other entities and events that hold a player name, they follow the rules for the Player `playerName`
+const PLAYER_NAME_FIELDS: [&str; 4] = ["playerName", "planter", "defuser", "carrier"];
+
+/// Fields that are the position in another form, like pixels on the radar or the callout zone, they go wherever `pos` goes
+const DERIVED_POS_FIELDS: [&str; 5] = ["radarPos", "trail", "filteredPos", "predictedPos", "zone"];
+
+fn is_derived_pos(field: &str) -> bool {
+    DERIVED_POS_FIELDS.contains(&field)
+}
+
+/// Which parts of a frame a client wants to receive, anything left out is sent in full.
+///
+/// Example: `{"entities": ["Player", "Bomb"], "fields": {"Player": ["pos", "yaw", "playerType"]}, "exclude": ["money"]}`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Entity types to send, like "Player" or "Bomb"
+    #[serde(default)]
+    pub entities: Option<HashSet<String>>,
+
+    /// Fields to keep, per entity type
+    #[serde(default)]
+    pub fields: HashMap<String, HashSet<String>>,
+
+    /// Fields removed from every entity and event
+    #[serde(default)]
+    pub exclude: HashSet<String>,
+}
+
+impl Subscription {
+    /// True if nothing gets filtered, lets the caller skip the projection
+    pub fn is_full(&self) -> bool {
+        self.entities.is_none() && self.fields.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Strips everything that isn't subscribed from a serialized `RadarData`
+    pub fn apply(&self, radar: &mut Value) {
+        if let Some(radar) = radar.as_object_mut() {
+            // Team economy is summed up player money, it goes wherever that goes
+            if !self.allows_field("Player", "money") {
+                radar.remove("economy");
+            }
+
+            // The summary names players and is derived from their positions
+            if !self.allows_field("Player", "pos") || !self.allows_field("Player", "playerName") {
+                radar.remove("summary");
+            } else if let Some(summary) = radar.get_mut("summary") {
+                self.apply_object(summary);
+            }
+
+            // Heat points are guesses where enemy players are, they follow the player position rules
+            if !self.allows_field("Player", "pos") {
+                radar.remove("heat");
+            } else if let Some(heat) = radar.get_mut("heat").and_then(Value::as_array_mut) {
+                for point in heat.iter_mut() {
+                    if let Some(point) = point.as_object_mut() {
+                        point.retain(|field, _| !matches!(field.as_str(), "playerName" | "radarPos") || self.allows_field("Player", field));
+                    }
+
+                    self.apply_object(point);
+                }
+            }
+        }
+
+        let Some(entities) = radar.get_mut("entityData").and_then(Value::as_array_mut) else {
+            return;
+        };
+
+        // Entities are externally tagged, {"Player": {...}}
+        entities.retain(|entity| {
+            match entity.as_object().and_then(|entity| entity.keys().next()) {
+                // Markers are where players were last seen
+                Some(kind) if kind == "Marker" => self.allows_entity(kind) && self.allows_field("Player", "pos"),
+                Some(kind) => self.allows_entity(kind),
+                None => false,
+            }
+        });
+
+        for entity in entities.iter_mut().filter_map(Value::as_object_mut) {
+            for (kind, inner) in entity.iter_mut() {
+                self.apply_entity(kind, inner);
+
+                if kind != "Player" {
+                    self.strip_player_names(inner);
+                }
+            }
+        }
+    }
+
+    /// Strips a serialized `GameEvent`, returns false if nothing worth sending is left
+    pub fn apply_event(&self, event: &mut Value) -> bool {
+        if !self.allows_field("Player", "playerName") {
+            // Without the name all that's left is that someone died or spawned
+            if matches!(event.get("type").and_then(Value::as_str), Some("PlayerDeath" | "PlayerSpawn")) {
+                return false;
+            }
+
+            self.strip_player_names(event);
+        }
+
+        self.apply_object(event);
+        true
+    }
+
+    /// Removes the names of players from an object that isn't a player, like a bomb planter or hostage carrier
+    fn strip_player_names(&self, object: &mut Value) {
+        if self.allows_field("Player", "playerName") {
+            return;
+        }
+
+        if let Some(object) = object.as_object_mut() {
+            object.retain(|field, _| !PLAYER_NAME_FIELDS.contains(&field.as_str()));
+        }
+    }
+
+    /// True if entities of this kind are sent at all
+    pub fn allows_entity(&self, kind: &str) -> bool {
+        self.entities.as_ref().map_or(true, |allowed| allowed.contains(kind))
+    }
+
+    /// True if `field` of entities of this kind survives the projection
+    pub fn allows_field(&self, kind: &str, field: &str) -> bool {
+        let allows = |field: &str| {
+            self.allows_entity(kind)
+                && self.fields.get(kind).map_or(true, |fields| fields.contains(field))
+                && !self.exclude.contains(field)
+        };
+
+        allows(field) && (!is_derived_pos(field) || allows("pos"))
+    }
+
+    /// Projects a single object as if it were an entity of this kind, like a scoreboard entry as a "Player"
+    pub fn apply_entity(&self, kind: &str, object: &mut Value) {
+        if let (Some(fields), Some(inner)) = (self.fields.get(kind), object.as_object_mut()) {
+            inner.retain(|field, _| fields.contains(field) && (!is_derived_pos(field) || fields.contains("pos")));
+        }
+
+        self.apply_object(object);
+    }
+
+    /// Strips excluded fields from a serialized object and everything nested in it, like a `GameEvent`
+    pub fn apply_object(&self, object: &mut Value) {
+        let excludes_pos = self.exclude.contains("pos");
+
+        match object {
+            Value::Object(inner) => {
+                inner.retain(|field, _| !self.exclude.contains(field) && !(excludes_pos && is_derived_pos(field)));
+                inner.values_mut().for_each(|value| self.apply_object(value));
+            },
+            Value::Array(items) => items.iter_mut().for_each(|value| self.apply_object(value)),
+            _ => {},
+        }
+    }
+}
+
+/// What the operator allows a token to see and do
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenConfig {
+    /// Applied on top of whatever the client subscribes to
+    #[serde(default)]
+    pub subscription: Subscription,
+
+    /// Allows toggling money reveal and changing the perspective
+    #[serde(default)]
+    pub control: bool,
+}
+
+/// Loads a JSON object mapping tokens to their [`TokenConfig`]
+pub fn load_tokens(path: &Path) -> anyhow::Result<HashMap<String, TokenConfig>> {
+    let content = std::fs::read_to_string(path)?;
+    let tokens: HashMap<String, TokenConfig> = serde_json::from_str(&content)?;
+
+    log::info!("Loaded {} access tokens from {}", tokens.len(), path.display());
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn subscription(value: Value) -> Subscription {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn field_whitelist_applies_to_entries() {
+        let sub = subscription(json!({"fields": {"Player": ["pos"]}}));
+        let mut entry = json!({"playerName": "a", "alive": true, "stats": {"kills": 3}});
+        sub.apply_entity("Player", &mut entry);
+
+        assert_eq!(entry, json!({}));
+    }
+
+    #[test]
+    fn exclude_reaches_nested_objects() {
+        let sub = subscription(json!({"exclude": ["ping"]}));
+        let mut entry = json!({"playerName": "a", "stats": {"kills": 3, "ping": 20}});
+        sub.apply_entity("Player", &mut entry);
+
+        assert_eq!(entry, json!({"playerName": "a", "stats": {"kills": 3}}));
+    }
+
+    #[test]
+    fn allows_field_follows_all_rules() {
+        let sub = subscription(json!({"entities": ["Player"], "fields": {"Player": ["pos", "money"]}, "exclude": ["money"]}));
+
+        assert!(sub.allows_field("Player", "pos"));
+        assert!(!sub.allows_field("Player", "money"));
+        assert!(!sub.allows_field("Player", "playerName"));
+        assert!(!sub.allows_field("Bomb", "pos"));
+    }
+
+    #[test]
+    fn economy_follows_player_money() {
+        let frame = json!({"entityData": [], "economy": {"ct": {"money": 8000}}});
+
+        for restricted in [json!({"exclude": ["money"]}), json!({"entities": ["Bomb"]}), json!({"fields": {"Player": ["pos"]}})] {
+            let mut value = frame.clone();
+            subscription(restricted).apply(&mut value);
+            assert!(value.get("economy").is_none());
+        }
+
+        let mut value = frame.clone();
+        subscription(json!({"exclude": ["health"]})).apply(&mut value);
+        assert!(value.get("economy").is_some());
+    }
+
+    #[test]
+    fn summary_needs_player_positions_and_names() {
+        let frame = json!({"entityData": [], "summary": {"players": [{"playerName": "a", "zone": "Mid", "distance": 300.0}]}});
+
+        for restricted in [json!({"entities": ["Bomb"]}), json!({"fields": {"Player": ["pos"]}}), json!({"exclude": ["pos"]})] {
+            let mut value = frame.clone();
+            subscription(restricted).apply(&mut value);
+            assert!(value.get("summary").is_none());
+        }
+
+        let mut value = frame.clone();
+        subscription(json!({"exclude": ["distance"]})).apply(&mut value);
+        assert_eq!(value["summary"], json!({"players": [{"playerName": "a", "zone": "Mid"}]}));
+    }
+
+    #[test]
+    fn heat_follows_player_fields() {
+        let frame = json!({"entityData": [], "heat": [{"pos": {"x": 1.0}, "radius": 64.0, "weight": 1.0, "playerName": "a", "radarPos": {"x": 2.0}}]});
+
+        for restricted in [json!({"entities": ["Bomb"]}), json!({"fields": {"Player": ["playerName"]}}), json!({"exclude": ["pos"]})] {
+            let mut value = frame.clone();
+            subscription(restricted).apply(&mut value);
+            assert!(value.get("heat").is_none());
+        }
+
+        let mut value = frame.clone();
+        subscription(json!({"fields": {"Player": ["pos"]}, "exclude": ["weight"]})).apply(&mut value);
+        assert_eq!(value["heat"], json!([{"pos": {"x": 1.0}, "radius": 64.0}]));
+    }
+
+    #[test]
+    fn events_follow_player_names() {
+        let death = json!({"type": "PlayerDeath", "tick": 1, "playerName": "a"});
+        let pickup = json!({"type": "BombPickup", "tick": 1, "playerName": "a"});
+
+        for restricted in [json!({"entities": ["Bomb"]}), json!({"fields": {"Player": ["pos"]}}), json!({"exclude": ["playerName"]})] {
+            let sub = subscription(restricted);
+
+            assert!(!sub.apply_event(&mut death.clone()));
+
+            let mut event = pickup.clone();
+            assert!(sub.apply_event(&mut event));
+            assert_eq!(event, json!({"type": "BombPickup", "tick": 1}));
+        }
+
+        let mut event = death.clone();
+        assert!(subscription(json!({"exclude": ["health"]})).apply_event(&mut event));
+        assert_eq!(event, death);
+    }
+
+    #[test]
+    fn other_entities_lose_player_names() {
+        let frame = json!({"entityData": [
+            {"Bomb": {"pos": {"x": 1.0}, "planter": "a", "defuser": "b", "defuserHasKit": true}},
+            {"Hostage": {"pos": {"x": 2.0}, "carrier": "c"}},
+        ]});
+
+        let mut value = frame.clone();
+        subscription(json!({"entities": ["Bomb", "Hostage"]})).apply(&mut value);
+        assert_eq!(value["entityData"], json!([
+            {"Bomb": {"pos": {"x": 1.0}, "defuserHasKit": true}},
+            {"Hostage": {"pos": {"x": 2.0}}},
+        ]));
+
+        let mut value = frame.clone();
+        subscription(json!({"exclude": ["money"]})).apply(&mut value);
+        assert_eq!(value, frame);
+    }
+
+    #[test]
+    fn markers_follow_player_positions() {
+        let frame = json!({"entityData": [{"Marker": {"pos": {"x": 1.0}, "playerName": "a", "age": 2.0}}]});
+
+        for restricted in [json!({"entities": ["Marker"]}), json!({"fields": {"Player": ["playerName"]}}), json!({"exclude": ["pos"]})] {
+            let mut value = frame.clone();
+            subscription(restricted).apply(&mut value);
+            assert_eq!(value["entityData"], json!([]));
+        }
+
+        let mut value = frame.clone();
+        subscription(json!({"fields": {"Player": ["pos"]}})).apply(&mut value);
+        assert_eq!(value["entityData"], json!([{"Marker": {"pos": {"x": 1.0}, "age": 2.0}}]));
+    }
+
+    #[test]
+    fn derived_positions_follow_pos() {
+        let player = json!({"pos": {"x": 1.0}, "radarPos": {"x": 2.0}, "trail": [], "filteredPos": {"x": 3.0}, "predictedPos": {"x": 4.0}, "health": 100});
+
+        let mut entry = player.clone();
+        subscription(json!({"exclude": ["pos"]})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({"health": 100}));
+
+        let mut entry = player.clone();
+        subscription(json!({"fields": {"Player": ["radarPos", "trail", "health"]}})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({"health": 100}));
+
+        let mut entry = player.clone();
+        subscription(json!({"fields": {"Player": ["pos", "radarPos"]}})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({"pos": {"x": 1.0}, "radarPos": {"x": 2.0}}));
+
+        let sub = subscription(json!({"exclude": ["pos"]}));
+        assert!(!sub.allows_field("Player", "radarPos"));
+        assert!(!sub.allows_field("Bomb", "predictedPos"));
+        assert!(sub.allows_field("Player", "health"));
+    }
+
+    #[test]
+    fn zone_follows_pos() {
+        let player = json!({"pos": {"x": 1.0}, "zone": "Mid", "health": 100});
+
+        let mut entry = player.clone();
+        subscription(json!({"exclude": ["pos"]})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({"health": 100}));
+
+        let mut entry = player.clone();
+        subscription(json!({"fields": {"Player": ["zone"]}})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({}));
+
+        let mut entry = player.clone();
+        subscription(json!({"exclude": ["health"]})).apply_entity("Player", &mut entry);
+        assert_eq!(entry, json!({"pos": {"x": 1.0}, "zone": "Mid"}));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{structs::Vec3, enums::BombSite};
+
+/// Players closer than this to a bombsite center count as being on the site
+const SITE_RADIUS: f32 = 800.0;
+
+/// What the summary needs to know about an alive player
+#[derive(Debug, Clone)]
+pub struct SummaryPlayer {
+    pub name: String,
+    pub pos: Vec3,
+    pub friendly: bool,
+    pub zone: Option<String>,
+    pub has_bomb: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AreaCount {
+    pub friendly: usize,
+    pub enemy: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteCount {
+    pub site: BombSite,
+
+    #[serde(flatten)]
+    pub count: AreaCount,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpponentDistance {
+    #[serde(rename = "playerName")]
+    pub player_name: String,
+
+    /// Straight line distance in units
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSummary {
+    #[serde(rename = "playerName")]
+    pub player_name: String,
+
+    pub friendly: bool,
+    pub zone: Option<String>,
+
+    /// Closest player of the other side
+    pub nearest: Option<String>,
+
+    /// Distance to `nearest` in units
+    pub distance: Option<f32>,
+
+    /// Every player of the other side, closest first
+    pub opponents: Vec<OpponentDistance>,
+}
+
+/// Compact text-HUD friendly overview of the current round
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TacticalSummary {
+    pub sites: Vec<SiteCount>,
+    pub zones: BTreeMap<String, AreaCount>,
+    pub players: Vec<PlayerSummary>,
+
+    #[serde(rename = "bombCarrier")]
+    pub bomb_carrier: Option<String>,
+
+    #[serde(rename = "bombCarrierZone")]
+    pub bomb_carrier_zone: Option<String>,
+}
+
+fn count(area: &mut AreaCount, friendly: bool) {
+    if friendly {
+        area.friendly += 1;
+    } else {
+        area.enemy += 1;
+    }
+}
+
+pub fn compute(players: &[SummaryPlayer], bombsites: &[(BombSite, Vec3)]) -> TacticalSummary {
+    let mut summary = TacticalSummary::default();
+
+    for (site, center) in bombsites {
+        let mut site_count = AreaCount::default();
+
+        for player in players.iter().filter(|player| player.pos.distance(center) <= SITE_RADIUS) {
+            count(&mut site_count, player.friendly);
+        }
+
+        summary.sites.push(SiteCount { site: *site, count: site_count });
+    }
+
+    for player in players {
+        if let Some(zone) = &player.zone {
+            count(summary.zones.entry(zone.clone()).or_default(), player.friendly);
+        }
+
+        if player.has_bomb {
+            summary.bomb_carrier = Some(player.name.clone());
+            summary.bomb_carrier_zone = player.zone.clone();
+        }
+
+        let mut opponents: Vec<OpponentDistance> = players
+            .iter()
+            .filter(|other| other.friendly != player.friendly)
+            .map(|other| OpponentDistance {
+                player_name: other.name.clone(),
+                distance: player.pos.distance(&other.pos).round(),
+            })
+            .collect();
+
+        opponents.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        summary.players.push(PlayerSummary {
+            player_name: player.name.clone(),
+            friendly: player.friendly,
+            zone: player.zone.clone(),
+            nearest: opponents.first().map(|opponent| opponent.player_name.clone()),
+            distance: opponents.first().map(|opponent| opponent.distance),
+            opponents,
+        });
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, x: f32, friendly: bool, zone: Option<&str>, has_bomb: bool) -> SummaryPlayer {
+        SummaryPlayer {
+            name: name.to_string(),
+            pos: Vec3 { x, y: 0.0, z: 0.0 },
+            friendly,
+            zone: zone.map(str::to_string),
+            has_bomb,
+        }
+    }
+
+    #[test]
+    fn site_counts_use_radius() {
+        let players = [
+            player("a", 0.0, true, None, false),
+            player("b", SITE_RADIUS, false, None, false),
+            player("c", SITE_RADIUS + 1.0, false, None, false),
+        ];
+
+        let summary = compute(&players, &[(BombSite::A, Vec3::default())]);
+        let site = &summary.sites[0];
+
+        assert_eq!(site.site, BombSite::A);
+        assert_eq!((site.count.friendly, site.count.enemy), (1, 1));
+    }
+
+    #[test]
+    fn opponents_sorted_by_distance() {
+        let players = [
+            player("a", 0.0, true, None, false),
+            player("b", 300.0, false, None, false),
+            player("c", -100.0, false, None, false),
+            player("d", 50.0, true, None, false),
+        ];
+
+        let summary = compute(&players, &[]);
+        let a = &summary.players[0];
+
+        assert_eq!(a.nearest.as_deref(), Some("c"));
+        assert_eq!(a.distance, Some(100.0));
+
+        let opponents: Vec<(&str, f32)> = a.opponents.iter().map(|opponent| (opponent.player_name.as_str(), opponent.distance)).collect();
+        assert_eq!(opponents, vec![("c", 100.0), ("b", 300.0)]);
+
+        let b = &summary.players[1];
+        assert_eq!(b.nearest.as_deref(), Some("d"));
+        assert_eq!(b.distance, Some(250.0));
+    }
+
+    #[test]
+    fn without_opponents_nothing_is_nearest() {
+        let summary = compute(&[player("a", 0.0, true, None, false)], &[]);
+
+        assert!(summary.players[0].nearest.is_none());
+        assert!(summary.players[0].distance.is_none());
+        assert!(summary.players[0].opponents.is_empty());
+    }
+
+    #[test]
+    fn bomb_carrier_and_zones() {
+        let players = [
+            player("a", 0.0, false, Some("Mid"), true),
+            player("b", 10.0, false, Some("Mid"), false),
+            player("c", 20.0, true, Some("A Site"), false),
+            player("d", 30.0, true, None, false),
+        ];
+
+        let summary = compute(&players, &[]);
+
+        assert_eq!(summary.bomb_carrier.as_deref(), Some("a"));
+        assert_eq!(summary.bomb_carrier_zone.as_deref(), Some("Mid"));
+        assert_eq!((summary.zones["Mid"].friendly, summary.zones["Mid"].enemy), (0, 2));
+        assert_eq!((summary.zones["A Site"].friendly, summary.zones["A Site"].enemy), (1, 0));
+        assert_eq!(summary.zones.len(), 2);
+    }
+}
@@ -1,16 +1,18 @@
 use std::{sync::Arc, path::PathBuf, collections::HashMap};
 use axum::{
-    extract::{ws::{WebSocketUpgrade, WebSocket, Message}, State},
-    response::Response,
-    routing::get,
-    Router,
+    extract::{ws::{WebSocketUpgrade, WebSocket, Message}, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
 };
+use clap::ValueEnum;
 use flate2::{write::GzEncoder, Compression};
 use std::io::Write;
-use tokio::sync::{RwLock, Mutex};
+use tokio::sync::{broadcast, RwLock, Mutex};
 use tower_http::services::ServeDir;
 
-use crate::comms::{RadarData};
+use crate::{comms::RadarData, enums::Perspective, events::EventSender, maps::ArcMapRegistry, nav::{self, ArcNavStore, PathQuery}, render, subscription::{Subscription, TokenConfig}};
 
 struct ClientState {
     last_entity_count: usize,
@@ -22,14 +24,172 @@ struct ClientState {
 struct AppState {
     data_lock: Arc<RwLock<RadarData>>,
     clients: Arc<Mutex<HashMap<String, ClientState>>>,
+    events: EventSender,
+    /// Only set if the operator configured access tokens
+    tokens: Option<Arc<HashMap<String, TokenConfig>>>,
+    maps: ArcMapRegistry,
+    navs: ArcNavStore,
+    backgrounds: Arc<render::BackgroundCache>,
 }
 
-async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+/// Looks up the `token` query parameter, errors if tokens are configured and it isn't valid
+fn authorize(state: &AppState, params: &HashMap<String, String>) -> Result<Option<TokenConfig>, StatusCode> {
+    let Some(tokens) = &state.tokens else {
+        return Ok(None);
+    };
+
+    params
+        .get("token")
+        .and_then(|token| tokens.get(token))
+        .map(|config| Some(config.clone()))
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+async fn ws_handler(ws: WebSocketUpgrade, Query(params): Query<HashMap<String, String>>, State(state): State<AppState>) -> Response {
+    let restriction = match authorize(&state, &params) {
+        Ok(restriction) => restriction,
+        Err(status) => {
+            log::info!("Rejected websocket client without a valid token");
+            return status.into_response();
+        }
+    };
+
     let clone = state.clone();
-    ws.on_upgrade(|socket| handle_socket(socket, clone))
+    ws.on_upgrade(|socket| handle_socket(socket, clone, restriction))
+}
+
+async fn scoreboard_handler(Query(params): Query<HashMap<String, String>>, State(state): State<AppState>) -> Response {
+    let restriction = match authorize(&state, &params) {
+        Ok(restriction) => restriction,
+        Err(status) => return status.into_response(),
+    };
+
+    // The scoreboard is a list of players, tokens that can't see players get nothing
+    if restriction.as_ref().is_some_and(|restriction| !restriction.subscription.allows_entity("Player")) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let scoreboard = state.data_lock.read().await.scoreboard.clone();
+    let Ok(mut scoreboard) = serde_json::to_value(scoreboard) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    if let (Some(restriction), Some(entries)) = (restriction, scoreboard.as_array_mut()) {
+        entries
+            .iter_mut()
+            .for_each(|entry| restriction.subscription.apply_entity("Player", entry));
+    }
+
+    Json(scoreboard).into_response()
+}
+
+async fn maps_handler(Query(params): Query<HashMap<String, String>>, State(state): State<AppState>) -> Response {
+    if let Err(status) = authorize(&state, &params) {
+        return status.into_response();
+    }
+
+    Json(state.maps.read().await.maps()).into_response()
+}
+
+async fn map_handler(Path(name): Path<String>, Query(params): Query<HashMap<String, String>>, State(state): State<AppState>) -> Response {
+    if let Err(status) = authorize(&state, &params) {
+        return status.into_response();
+    }
+
+    match state.maps.read().await.get(&name) {
+        Some(entry) => Json(entry.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn path_handler(Query(params): Query<HashMap<String, String>>, State(state): State<AppState>, Json(query): Json<PathQuery>) -> Response {
+    if let Err(status) = authorize(&state, &params) {
+        return status.into_response();
+    }
+
+    let current = state.data_lock.read().await.get_map_name().to_string();
+    let map = query.map.unwrap_or_else(|| current.clone());
+
+    // Every name the nav store sees stays cached, so only maps that exist get through
+    if map != current && state.maps.read().await.get(&map).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let Some(mesh) = nav::get(&state.navs, &map).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match mesh.path(&query.from, &query.to) {
+        Some(path) => Json(path).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn render_handler(Query(params): Query<HashMap<String, String>>, State(state): State<AppState>) -> Response {
+    let restriction = match authorize(&state, &params) {
+        Ok(restriction) => restriction,
+        Err(status) => return status.into_response(),
+    };
+
+    // The image can't leave out fields, so only tokens that see everything may render
+    if restriction.is_some_and(|restriction| !restriction.subscription.is_full()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let size = params
+        .get("size")
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(render::DEFAULT_SIZE)
+        .clamp(render::MIN_SIZE, render::MAX_SIZE);
+
+    let frame = state.data_lock.read().await.clone();
+    if !frame.is_ingame() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let (info, image_path) = {
+        let maps = state.maps.read().await;
+        let name = frame.get_map_name();
+        let info = maps.get(name).and_then(|entry| entry.info.clone());
+        let layer = info.as_ref().and_then(|info| render::current_layer(&frame, info));
+        let image_path = maps.layer_image_path(name, layer);
+        (info, image_path)
+    };
+
+    let Some(info) = info else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let backgrounds = state.backgrounds.clone();
+    match tokio::task::spawn_blocking(move || render::render_png(&frame, &info, &image_path, &backgrounds, size)).await {
+        Ok(Ok(png)) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+        Ok(Err(e)) => {
+            log::warn!("Failed to render frame: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        },
+        Err(e) => {
+            log::warn!("Render task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        },
+    }
+}
+
+fn serialize_frame(radar_data: &RadarData, subscription: &Subscription, restriction: Option<&TokenConfig>) -> serde_json::Result<String> {
+    if subscription.is_full() && restriction.map_or(true, |r| r.subscription.is_full()) {
+        return serde_json::to_string(radar_data);
+    }
+
+    let mut value = serde_json::to_value(radar_data)?;
+    subscription.apply(&mut value);
+
+    if let Some(restriction) = restriction {
+        restriction.subscription.apply(&mut value);
+    }
+
+    Ok(value.to_string())
 }
 
-async fn handle_socket(mut socket: WebSocket, state: AppState) {
+async fn handle_socket(mut socket: WebSocket, state: AppState, restriction: Option<TokenConfig>) {
     let client_id = uuid::Uuid::new_v4().to_string();
 
     {
@@ -45,109 +205,203 @@ async fn handle_socket(mut socket: WebSocket, state: AppState) {
     let mut frame_counter = 0;
     let mut skip_frames = false;
 
-    while let Some(msg) = socket.recv().await {
-        if let Ok(msg) = msg {
-            if let Ok(text) = msg.to_text() {
-                if text == "requestInfo" {
-                    frame_counter += 1;
-                    if skip_frames && frame_counter % 2 != 0 {
-                        continue;
-                    }
+    let mut events = state.events.subscribe();
 
-                    let radar_data = state.data_lock.read().await;
-                    let mut clients = state.clients.lock().await;
-                    let client_state = clients.get_mut(&client_id).unwrap();
+    let mut subscription = Subscription::default();
+    let can_control = restriction.as_ref().map_or(true, |r| r.control);
 
-                    let entity_count = radar_data.get_entities().len();
+    loop {
+        tokio::select! {
+            msg = socket.recv() => {
+                let Some(Ok(msg)) = msg else {
+                    break;
+                };
 
-                    if entity_count > 5 && !skip_frames && client_state.ping_ms > 100 {
-                        skip_frames = true;
-                        log::info!("Enabling frame skipping for high latency client");
-                    }
+                if let Ok(text) = msg.to_text() {
+                    if text == "requestInfo" {
+                        frame_counter += 1;
+                        if skip_frames && frame_counter % 2 != 0 {
+                            continue;
+                        }
+
+                        let radar_data = state.data_lock.read().await;
+                        let mut clients = state.clients.lock().await;
+                        let client_state = clients.get_mut(&client_id).unwrap();
+
+                        let entity_count = radar_data.get_entities().len();
+
+                        if entity_count > 5 && !skip_frames && client_state.ping_ms > 100 {
+                            skip_frames = true;
+                            log::info!("Enabling frame skipping for high latency client");
+                        }
+
+                        client_state.last_entity_count = entity_count;
+
+                        let Ok(json) = serialize_frame(&radar_data, &subscription, restriction.as_ref()) else {
+                            continue;
+                        };
+
+                        compression_buffer.clear();
+
+                        let compression_level = if json.len() > 20000 || client_state.high_latency {
+                            Compression::best()
+                        } else if json.len() > 5000 {
+                            Compression::default()
+                        } else {
+                            Compression::fast()
+                        };
 
-                    client_state.last_entity_count = entity_count;
-
-                    let Ok(json) = serde_json::to_string(&*radar_data) else {
-                        continue;
-                    };
-
-                    compression_buffer.clear();
-
-                    let compression_level = if json.len() > 20000 || client_state.high_latency {
-                        Compression::best()
-                    } else if json.len() > 5000 {
-                        Compression::default()
-                    } else {
-                        Compression::fast()
-                    };
-
-                    let mut encoder = GzEncoder::new(Vec::new(), compression_level);
-                    if encoder.write_all(json.as_bytes()).is_ok() {
-                        match encoder.finish() {
-                            Ok(compressed) => {
-                                if compressed.len() < json.len() {
-                                    let mut message = vec![0x01];
-                                    message.extend_from_slice(&compressed);
-                                    let _ = socket.send(Message::Binary(message)).await;
-                                } else {
+                        let mut encoder = GzEncoder::new(Vec::new(), compression_level);
+                        if encoder.write_all(json.as_bytes()).is_ok() {
+                            match encoder.finish() {
+                                Ok(compressed) => {
+                                    if compressed.len() < json.len() {
+                                        let mut message = vec![0x01];
+                                        message.extend_from_slice(&compressed);
+                                        let _ = socket.send(Message::Binary(message)).await;
+                                    } else {
+                                        let mut uncompressed = vec![0x00];
+                                        uncompressed.extend_from_slice(json.as_bytes());
+                                        let _ = socket.send(Message::Binary(uncompressed)).await;
+                                    }
+                                },
+                                Err(_) => {
                                     let mut uncompressed = vec![0x00];
                                     uncompressed.extend_from_slice(json.as_bytes());
                                     let _ = socket.send(Message::Binary(uncompressed)).await;
                                 }
+                            }
+                        } else {
+                            let mut uncompressed = vec![0x00];
+                            uncompressed.extend_from_slice(json.as_bytes());
+                            let _ = socket.send(Message::Binary(uncompressed)).await;
+                        }
+                    } else if let Some(subscription_str) = text.strip_prefix("subscribe:") {
+                        let response = match serde_json::from_str::<Subscription>(subscription_str) {
+                            Ok(new_subscription) => {
+                                subscription = new_subscription;
+
+                                serde_json::json!({
+                                    "action": "subscribe",
+                                    "status": "success",
+                                })
+                            },
+                            Err(e) => {
+                                serde_json::json!({
+                                    "action": "subscribe",
+                                    "status": "error",
+                                    "message": e.to_string()
+                                })
+                            }
+                        };
+
+                        let _ = socket.send(Message::Text(response.to_string())).await;
+                    } else if !can_control && (text == "toggleMoneyReveal" || text.starts_with("setPerspective:")) {
+                        let action = text.split(':').next().unwrap_or(text);
+                        let response = serde_json::json!({
+                            "action": action,
+                            "status": "forbidden"
+                        });
+
+                        let _ = socket.send(Message::Text(response.to_string())).await;
+                    } else if text == "toggleMoneyReveal" {
+                        let new_value = {
+                            let mut data = state.data_lock.write().await;
+                            data.money_reveal_enabled = !data.money_reveal_enabled;
+                            data.money_reveal_enabled
+                        };
+
+                        let response = serde_json::json!({
+                            "action": "toggleMoneyReveal",
+                            "status": "success",
+                            "enabled": new_value
+                        });
+
+                        let _ = socket.send(Message::Text(response.to_string())).await;
+                    } else if let Some(perspective_str) = text.strip_prefix("setPerspective:") {
+                        let response = match Perspective::from_str(perspective_str, true) {
+                            Ok(perspective) => {
+                                state.data_lock.write().await.perspective = perspective;
+
+                                serde_json::json!({
+                                    "action": "setPerspective",
+                                    "status": "success",
+                                    "perspective": perspective
+                                })
                             },
                             Err(_) => {
-                                let mut uncompressed = vec![0x00];
-                                uncompressed.extend_from_slice(json.as_bytes());
-                                let _ = socket.send(Message::Binary(uncompressed)).await;
+                                serde_json::json!({
+                                    "action": "setPerspective",
+                                    "status": "error",
+                                })
                             }
-                        }
-                    } else {
-                        let mut uncompressed = vec![0x00];
-                        uncompressed.extend_from_slice(json.as_bytes());
-                        let _ = socket.send(Message::Binary(uncompressed)).await;
-                    }
-                } else if text == "toggleMoneyReveal" {
-                    let new_value = {
-                        let mut data = state.data_lock.write().await;
-                        data.money_reveal_enabled = !data.money_reveal_enabled;
-                        data.money_reveal_enabled
-                    };
-
-                    let response = serde_json::json!({
-                        "action": "toggleMoneyReveal",
-                        "status": "success",
-                        "enabled": new_value
-                    });
-
-                    let _ = socket.send(Message::Text(response.to_string())).await;
-                } else if text.starts_with("ping:") {
-                    if let Some(ping_str) = text.strip_prefix("ping:") {
-                        if let Ok(ping_ms) = ping_str.parse::<u32>() {
-                            let mut clients = state.clients.lock().await;
-                            if let Some(client) = clients.get_mut(&client_id) {
-                                client.ping_ms = ping_ms;
-                                client.high_latency = ping_ms > 100;
+                        };
+
+                        let _ = socket.send(Message::Text(response.to_string())).await;
+                    } else if text.starts_with("ping:") {
+                        if let Some(ping_str) = text.strip_prefix("ping:") {
+                            if let Ok(ping_ms) = ping_str.parse::<u32>() {
+                                let mut clients = state.clients.lock().await;
+                                if let Some(client) = clients.get_mut(&client_id) {
+                                    client.ping_ms = ping_ms;
+                                    client.high_latency = ping_ms > 100;
+                                }
                             }
                         }
+                        let _ = socket.send(Message::Text("pong".to_string())).await;
                     }
-                    let _ = socket.send(Message::Text("pong".to_string())).await;
+                }
+            },
+            event = events.recv() => {
+                match event {
+                    Ok(event) => {
+                        let Ok(mut event) = serde_json::to_value(&event) else {
+                            continue;
+                        };
+
+                        let keep = subscription.apply_event(&mut event)
+                            && restriction.as_ref().map_or(true, |restriction| restriction.subscription.apply_event(&mut event));
+
+                        if !keep {
+                            continue;
+                        }
+
+                        let message = serde_json::json!({
+                            "action": "event",
+                            "event": event
+                        });
+
+                        let _ = socket.send(Message::Text(message.to_string())).await;
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::debug!("Client lagged behind, skipped {} events", skipped);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-        } else {
-            break;
         }
     }
     let mut clients = state.clients.lock().await;
     clients.remove(&client_id);
 }
 
-pub async fn run(path: PathBuf, port: u16, data_lock: Arc<RwLock<RadarData>>) -> anyhow::Result<()> {
+pub async fn run(path: PathBuf, port: u16, data_lock: Arc<RwLock<RadarData>>, events: EventSender, tokens: Option<HashMap<String, TokenConfig>>, maps: ArcMapRegistry, navs: ArcNavStore) -> anyhow::Result<()> {
     let app = Router::new()
         .nest_service("/", ServeDir::new(path))
         .route("/ws", get(ws_handler))
+        .route("/api/scoreboard", get(scoreboard_handler))
+        .route("/api/maps", get(maps_handler))
+        .route("/api/maps/:name", get(map_handler))
+        .route("/api/path", post(path_handler))
+        .route("/api/render.png", get(render_handler))
         .with_state(AppState {
             data_lock,
-            clients: Arc::new(Mutex::new(HashMap::new()))
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            tokens: tokens.map(Arc::new),
+            maps,
+            navs,
+            backgrounds: Arc::new(render::BackgroundCache::default()),
         });
 
     let address = format!("0.0.0.0:{}", port);
@@ -3,6 +3,7 @@ const localColor = "#109856"
 const teamColor = "#68a3e5"
 const enemyColor = "#ec040b"
 const bombColor = "#eda338"
+const hostageColor = "#f2e6c9"
 const textColor = "#d1d1d1"
 
 const DEFAULT_TEXT_SIZE = 1.2;
@@ -36,7 +37,9 @@ const NETWORK_SETTINGS = {
     pingInterval: 3000,
     maxRetries: 5,
     requestTimeout: 5000,
-    reconnectDelay: 1000
+    reconnectDelay: 1000,
+    // How far past the last frame positions get extrapolated with the player's velocity
+    maxExtrapolationMs: 100
 };
 
 let connectionHealthy = true;
@@ -77,6 +80,11 @@ let ctx = null;
 let radarData = null;
 let freq = 0;
 let image = null;
+
+// Multi-level maps, the floor of the focused player is shown and everything else is dimmed
+let currentLayer = null;
+let layerImages = {};
+const OTHER_LAYER_ALPHA = 0.35;
 let map = null;
 let mapName = null;
 let loaded = false;
@@ -84,6 +92,7 @@ let entityData = null;
 let update = false;
 let localYaw = 0;
 let localPlayerPos = null;
+let observedPlayerName = null;
 
 /// Radarflow zoom in
 let zoomSet = false;
@@ -104,9 +113,14 @@ const weaponIdMap = {
 
 // Networking
 let websocket = null;
-const websocketAddr = location.protocol === 'https:'
+// Access token and field subscription can be passed in the page url, e.g. ?token=abc&subscribe={"exclude":["money"]}
+const pageParams = new URLSearchParams(window.location.search);
+const accessToken = pageParams.get("token");
+const initialSubscription = pageParams.get("subscribe");
+
+const websocketAddr = (location.protocol === 'https:'
     ? `wss://${window.location.host}/ws`
-    : `ws://${window.location.host}/ws`;
+    : `ws://${window.location.host}/ws`) + (accessToken ? `?token=${encodeURIComponent(accessToken)}` : "");
 
 // Util functions
 const clamp = (num, min, max) => Math.min(Math.max(num, min), max);
@@ -201,12 +215,26 @@ function getInterpolatedEntityData(entityId) {
 
     if (result.Player) {
         if (data.current.Player && data.target.Player) {
-            if (data.current.Player.pos && data.target.Player.pos) {
+            // Prefer the server side filtered positions when smoothing is enabled
+            const currentPos = data.current.Player.filteredPos || data.current.Player.pos;
+            const targetPos = data.target.Player.predictedPos || data.target.Player.pos;
+
+            if (currentPos && targetPos) {
                 result.Player.pos = lerpPosition(
-                    data.current.Player.pos,
-                    data.target.Player.pos,
+                    currentPos,
+                    targetPos,
                     easedT * NETWORK_SETTINGS.interpolationAmount
                 );
+
+                const vel = data.target.Player.vel;
+                if (t >= 1 && vel && !data.target.Player.predictedPos) {
+                    const extra = Math.min(elapsed - targetDuration, NETWORK_SETTINGS.maxExtrapolationMs) / 1000;
+                    result.Player.pos = {
+                        x: result.Player.pos.x + vel.x * extra,
+                        y: result.Player.pos.y + vel.y * extra,
+                        z: result.Player.pos.z + vel.z * extra
+                    };
+                }
             }
 
             if (data.current.Player.yaw !== undefined && data.target.Player.yaw !== undefined) {
@@ -299,6 +327,10 @@ function renderFrame() {
 
         drawImage();
 
+        drawRescueZones();
+
+        drawHeat();
+
         drawEntities();
 
         drawBombTimer();
@@ -321,6 +353,15 @@ function renderFrame() {
         else if (rotationDisabledUntilRespawn) rotationStatus = "Disabled (Death)";
 
         ctx.fillText(`${currentFps} FPS | ${freq} Hz | Ping: ${Math.round(pingTracker.getAveragePing())}ms | Rotation: ${rotationStatus}`, 10, fontSize + 4);
+
+        if (radarData && radarData.economy) {
+            const formatTeam = (team, eco) => `${team} $${eco.money} (${eco.buy}, min next $${eco.predictedMinMoney})`;
+            ctx.fillText(
+                `${formatTeam("CT", radarData.economy.ct)} | ${formatTeam("T", radarData.economy.t)}`,
+                10,
+                fontSize * 2 + 8
+            );
+        }
     }
 }
 
@@ -328,6 +369,7 @@ function processPlayerPositions() {
     if (!entityData) return;
 
     localPlayerPos = null;
+    observedPlayerName = null;
     focusedPlayerPos = null;
     focusedPlayerYaw = 0;
     let oldPlayerList = { ...playerList };
@@ -361,12 +403,25 @@ function processPlayerPositions() {
 
                 lastKnownPositions[player.playerName] = player.pos;
                 if (deadPlayers[player.playerName]) delete deadPlayers[player.playerName];
+
+                // While dead or spectating, the local view follows the observed player
+                if (player.isObserved) {
+                    observedPlayerName = player.playerName;
+                    newPlayerList[LOCAL_PLAYER_VIEW] = {
+                        pos: player.pos,
+                        yaw: player.yaw
+                    };
+
+                    lastKnownPositions[LOCAL_PLAYER_VIEW] = player.pos;
+                    if (deadPlayers[LOCAL_PLAYER_VIEW]) delete deadPlayers[LOCAL_PLAYER_VIEW];
+                }
             }
 
             if (player.playerName === focusedPlayerName ||
-                (focusedPlayerName === LOCAL_PLAYER_VIEW && player.playerType === "Local")) {
+                (focusedPlayerName === LOCAL_PLAYER_VIEW && (player.playerType === "Local" || player.isObserved))) {
                 focusedPlayerPos = player.pos;
                 focusedPlayerYaw = player.yaw;
+                currentLayer = player.layer || null;
 
                 if (rotationDisabledUntilRespawn) {
                     console.log("[radarflow] Player respawned, re-enabling rotation");
@@ -511,9 +566,12 @@ function drawImage() {
         ctx.translate(-canvas.width / 2, -canvas.height / 2);
     }
 
+    // Layer images share the calibration of the main image
+    const layerImage = (currentLayer && layerImages[currentLayer]) || image;
+
     ctx.drawImage(
-        image,
-        0, 0, image.width, image.height,
+        layerImage,
+        0, 0, layerImage.width, layerImage.height,
         0, 0, canvas.width, canvas.height
     );
 
@@ -714,8 +772,12 @@ function drawEntities() {
         let pos;
         if (renderEntity.Bomb) {
             pos = renderEntity.Bomb.pos;
+        } else if (renderEntity.Hostage) {
+            pos = renderEntity.Hostage.pos;
         } else if (renderEntity.Player) {
             pos = renderEntity.Player.pos;
+        } else if (renderEntity.Marker) {
+            pos = renderEntity.Marker.pos;
         } else {
             return;
         }
@@ -741,9 +803,23 @@ function drawEntities() {
             });
         }
 
+        const entityLayer = Object.values(renderEntity)[0].layer;
+        const onOtherLayer = currentLayer && entityLayer && entityLayer !== currentLayer;
+
+        if (isVisible && onOtherLayer) {
+            ctx.save();
+            ctx.globalAlpha = OTHER_LAYER_ALPHA;
+        }
+
         if (isVisible) {
             if (renderEntity.Bomb) {
-                drawBomb(renderEntity.Bomb.pos, renderEntity.Bomb.isPlanted);
+                drawBomb(renderEntity.Bomb.pos, renderEntity.Bomb.isPlanted, renderEntity.Bomb.plantProgress);
+            } else if (renderEntity.Hostage) {
+                if (renderEntity.Hostage.state !== "Rescued") {
+                    drawHostage(renderEntity.Hostage.pos, renderEntity.Hostage.state, renderEntity.Hostage.carrier);
+                }
+            } else if (renderEntity.Marker) {
+                drawMarker(renderEntity.Marker);
             } else if (renderEntity.Player) {
                 const player = renderEntity.Player;
                 let fillStyle = localColor;
@@ -753,6 +829,10 @@ function drawEntities() {
                     case "Enemy": fillStyle = enemyColor; break;
                 }
 
+                if (player.trail && player.trail.length > 1) {
+                    drawTrail(player.trail, fillStyle);
+                }
+
                 drawEntity(
                     player.pos,
                     fillStyle,
@@ -775,7 +855,9 @@ function drawEntities() {
                             player.playerType,
                             player.hasAwp,
                             player.hasBomb,
-                            player.isScoped
+                            player.isScoped,
+                            player.flags,
+                            player.zone
                         );
                     }
 
@@ -814,6 +896,10 @@ function drawEntities() {
                 }
             }
         }
+
+        if (isVisible && onOtherLayer) {
+            ctx.restore();
+        }
     });
 
     if (playerCentered && showOffscreenIndicators) {
@@ -904,6 +990,19 @@ function drawOffscreenIndicator(x, y, angle, color, opacity, player) {
     ctx.restore();
 }
 
+function getBombStatusText() {
+    const bombEntity = entityData ? entityData.find(entity => entity.Bomb) : null;
+    if (!bombEntity) return "";
+
+    const bomb = bombEntity.Bomb;
+    const parts = [];
+
+    if (bomb.site) parts.push(`${bomb.site} planted`);
+    if (bomb.defuser) parts.push(`${bomb.defuser} defusing${bomb.defuserHasKit ? " with kit" : ""}`);
+
+    return parts.length ? `${parts.join(" | ")} | ` : "";
+}
+
 function drawBombTimer() {
     if (!radarData || !radarData.bombPlanted || radarData.bombExploded || radarData.bombDefuseTimeleft < 0) {
         return;
@@ -931,7 +1030,7 @@ function drawBombTimer() {
     ctx.textAlign = "center";
     ctx.textBaseline = "middle";
     ctx.fillStyle = textColor;
-    ctx.fillText(`${timeleft.toFixed(1)}s`, 1024 / 2, timerY + timerHeight + fontSize / 2 + 4);
+    ctx.fillText(`${getBombStatusText()}${timeleft.toFixed(1)}s`, 1024 / 2, timerY + timerHeight + fontSize / 2 + 4);
 
     ctx.strokeStyle = "black";
     ctx.lineWidth = 2;
@@ -1072,7 +1171,34 @@ function mapCoordinates(coordinates) {
     return { x: offset_x, y: offset_y };
 }
 
-function drawPlayerName(pos, playerName, playerType, hasAwp, hasBomb, isScoped) {
+// Must match PlayerFlags in src/structs/player_flags.rs
+const PLAYER_FLAGS = {
+    FLASHED: 1 << 0,
+    DEFUSING: 1 << 1,
+    PLANTING: 1 << 2,
+    RELOADING: 1 << 3,
+    CROUCHING: 1 << 4,
+    WALKING: 1 << 5,
+    RUNNING: 1 << 6,
+    ON_GROUND: 1 << 7,
+    ON_LADDER: 1 << 8,
+};
+
+function getPlayerStatusTags(flags) {
+    if (!flags) return "";
+
+    const tags = [];
+    if (flags & PLAYER_FLAGS.FLASHED) tags.push("FLASHED");
+    if (flags & PLAYER_FLAGS.DEFUSING) tags.push("DEFUSING");
+    if (flags & PLAYER_FLAGS.PLANTING) tags.push("PLANTING");
+    if (flags & PLAYER_FLAGS.RELOADING) tags.push("RELOADING");
+    if (flags & PLAYER_FLAGS.ON_LADDER) tags.push("LADDER");
+    else if (!(flags & PLAYER_FLAGS.ON_GROUND)) tags.push("AIR");
+
+    return tags.map(tag => ` [${tag}]`).join("");
+}
+
+function drawPlayerName(pos, playerName, playerType, hasAwp, hasBomb, isScoped, flags, zone) {
     if (!map) return;
 
     const transformed = mapAndTransformCoordinates(pos);
@@ -1095,6 +1221,12 @@ function drawPlayerName(pos, playerName, playerType, hasAwp, hasBomb, isScoped)
         displayName += " [SCOPED]";
     }
 
+    displayName += getPlayerStatusTags(flags);
+
+    if (zone) {
+        displayName += ` @ ${zone}`;
+    }
+
     ctx.font = `bold ${textSize}px Arial`;
     ctx.textAlign = "center";
     ctx.textBaseline = "top";
@@ -1186,7 +1318,152 @@ function drawPlayerBomb(pos, playerType) {
     ctx.fillText("[C4]", mapPos.x, textY);
 }
 
-function drawBomb(pos, planted) {
+function drawRescueZones() {
+    if (!map || !radarData || !radarData.rescueZones) return;
+
+    radarData.rescueZones.forEach(zone => {
+        const transformed = mapAndTransformCoordinates(zone);
+        const mapPos = transformed.pos;
+        const size = minEntitySize * entitySizeMultiplier * 2.5;
+
+        ctx.beginPath();
+        ctx.arc(mapPos.x, mapPos.y, size, 0, 2 * Math.PI);
+        ctx.fillStyle = "rgba(50, 205, 50, 0.25)";
+        ctx.fill();
+        ctx.lineWidth = 2;
+        ctx.strokeStyle = "#32CD32";
+        ctx.stroke();
+        ctx.closePath();
+    });
+}
+
+function drawTrail(trail, color) {
+    if (!map) return;
+
+    ctx.save();
+    ctx.strokeStyle = color;
+    ctx.lineWidth = 2;
+    ctx.lineJoin = "round";
+
+    // Fade from the oldest point to the newest
+    for (let i = 1; i < trail.length; i++) {
+        const from = mapAndTransformCoordinates(trail[i - 1]).pos;
+        const to = mapAndTransformCoordinates(trail[i]).pos;
+
+        ctx.globalAlpha = 0.1 + 0.5 * (i / trail.length);
+        ctx.beginPath();
+        ctx.moveTo(from.x, from.y);
+        ctx.lineTo(to.x, to.y);
+        ctx.stroke();
+    }
+
+    ctx.restore();
+}
+
+// Probable positions of unseen enemies, sent if the server runs with --heat
+function drawHeat() {
+    if (!map || !radarData || !radarData.heat) return;
+
+    ctx.save();
+
+    radarData.heat.forEach(point => {
+        const center = mapAndTransformCoordinates(point.pos).pos;
+        const edge = mapAndTransformCoordinates({ x: point.pos.x + point.radius, y: point.pos.y, z: point.pos.z }).pos;
+        const radius = Math.max(Math.hypot(edge.x - center.x, edge.y - center.y), 2);
+
+        const gradient = ctx.createRadialGradient(center.x, center.y, 0, center.x, center.y, radius);
+        gradient.addColorStop(0, enemyColor);
+        gradient.addColorStop(1, "transparent");
+
+        ctx.globalAlpha = clamp(0.15 + point.weight * 0.6, 0.15, 0.6);
+        ctx.fillStyle = gradient;
+        ctx.beginPath();
+        ctx.arc(center.x, center.y, radius, 0, 2 * Math.PI);
+        ctx.fill();
+    });
+
+    ctx.restore();
+}
+
+function drawMarker(marker) {
+    if (!map) return;
+
+    const transformed = mapAndTransformCoordinates(marker.pos);
+    const mapPos = transformed.pos;
+    const size = minEntitySize * entitySizeMultiplier * 0.8;
+
+    let color = localColor;
+    switch (marker.playerType) {
+        case "Team": color = teamColor; break;
+        case "Enemy": color = enemyColor; break;
+    }
+
+    ctx.save();
+
+    if (marker.kind === "Death") {
+        ctx.globalAlpha = 0.6;
+        ctx.strokeStyle = color;
+        ctx.lineWidth = 3;
+        ctx.beginPath();
+        ctx.moveTo(mapPos.x - size, mapPos.y - size);
+        ctx.lineTo(mapPos.x + size, mapPos.y + size);
+        ctx.moveTo(mapPos.x + size, mapPos.y - size);
+        ctx.lineTo(mapPos.x - size, mapPos.y + size);
+        ctx.stroke();
+    } else {
+        // Last known positions fade out over time
+        ctx.globalAlpha = Math.max(0.2, 0.7 - marker.age / 20);
+        ctx.strokeStyle = color;
+        ctx.lineWidth = 2;
+        ctx.setLineDash([4, 3]);
+        ctx.beginPath();
+        ctx.arc(mapPos.x, mapPos.y, size, 0, 2 * Math.PI);
+        ctx.stroke();
+        ctx.setLineDash([]);
+
+        ctx.font = `${transformed.textSize * 0.8}px Arial`;
+        ctx.textAlign = "center";
+        ctx.textBaseline = "top";
+        ctx.fillStyle = color;
+        ctx.fillText(`${Math.round(marker.age)}s`, mapPos.x, mapPos.y + size + 4);
+    }
+
+    ctx.restore();
+}
+
+function drawHostage(pos, state, carrier) {
+    if (!map) return;
+
+    const transformed = mapAndTransformCoordinates(pos);
+    const mapPos = transformed.pos;
+    const size = minEntitySize * entitySizeMultiplier;
+
+    ctx.beginPath();
+    ctx.arc(mapPos.x, mapPos.y, size, 0, 2 * Math.PI);
+    ctx.fillStyle = hostageColor;
+    ctx.fill();
+
+    ctx.lineWidth = 3;
+    ctx.strokeStyle = state === "Following" ? teamColor : "black";
+    ctx.stroke();
+
+    ctx.font = `bold ${Math.max(size * 1.2, minTextSize)}px Arial`;
+    ctx.textAlign = "center";
+    ctx.textBaseline = "middle";
+    ctx.fillStyle = "black";
+    ctx.fillText("H", mapPos.x, mapPos.y);
+
+    ctx.closePath();
+
+    if (carrier && drawNames) {
+        ctx.font = `${transformed.textSize}px Arial`;
+        ctx.textBaseline = "top";
+        ctx.fillStyle = textColor;
+        ctx.fillText(carrier, mapPos.x, mapPos.y + size + 4);
+    }
+}
+
+function drawBomb(pos, planted, plantProgress) {
     if (!map) return;
 
     const transformed = mapAndTransformCoordinates(pos);
@@ -1217,6 +1494,14 @@ function drawBomb(pos, planted) {
         ctx.arc(mapPos.x, mapPos.y, size + 4, 0, 2 * Math.PI);
         ctx.stroke();
     }
+
+    if (typeof plantProgress === 'number') {
+        ctx.strokeStyle = enemyColor;
+        ctx.lineWidth = 3;
+        ctx.beginPath();
+        ctx.arc(mapPos.x, mapPos.y, size + 4, -Math.PI / 2, -Math.PI / 2 + plantProgress * 2 * Math.PI);
+        ctx.stroke();
+    }
 }
 
 function drawEntity(pos, fillStyle, dormant, hasBomb, yaw, hasAwp, playerType, isScoped, playerName, isPlanted, weaponId) {
@@ -1231,7 +1516,7 @@ function drawEntity(pos, fillStyle, dormant, hasBomb, yaw, hasAwp, playerType, i
     const arrowWidth = 35;
 
     const isFocusedPlayer = playerName === focusedPlayerName ||
-        (focusedPlayerName === LOCAL_PLAYER_VIEW && playerType === "Local");
+        (focusedPlayerName === LOCAL_PLAYER_VIEW && (playerType === "Local" || playerName === observedPlayerName));
     
     const isDeadPlayer = playerList[playerName]?.isDead || false;
 
@@ -1363,6 +1648,8 @@ function loadMap(mapName) {
             console.log("[radarflow] Map data loaded");
             map = data;
             update = true;
+
+            loadLayerImages(data.layers || []);
         })
         .catch(error => {
             console.error(`[radarflow] Error loading JSON: ${error}`);
@@ -1384,9 +1671,30 @@ function loadMap(mapName) {
     map_img.src = imagePath;
 }
 
+function loadLayerImages(layers) {
+    layerImages = {};
+
+    layers.filter(layer => layer.image).forEach(layer => {
+        const layerImg = new Image();
+
+        layerImg.onload = () => {
+            layerImages[layer.name] = layerImg;
+            update = true;
+        };
+
+        layerImg.onerror = () => {
+            console.warn(`[radarflow] Missing image for layer ${layer.name}, using the main image`);
+        };
+
+        layerImg.src = `assets/image/${layer.image}`;
+    });
+}
+
 function unloadMap() {
     ctx.clearRect(0, 0, canvas.width, canvas.height);
     map = null;
+    layerImages = {};
+    currentLayer = null;
     mapName = null;
     loaded = false;
     update = true;
@@ -1405,6 +1713,11 @@ function processData(data) {
     freq = data.freq;
     entityData = data.entityData;
 
+    if (data.perspective !== undefined) {
+        const select = document.getElementById("perspectiveSelect");
+        if (select && document.activeElement !== select) select.value = data.perspective.toLowerCase();
+    }
+
     if (data.money_reveal_enabled !== undefined) {
         const checkbox = document.getElementById("moneyReveal");
         if (checkbox) checkbox.checked = data.money_reveal_enabled;
@@ -1422,6 +1735,18 @@ function processData(data) {
     update = true;
 }
 
+function processEvent(event) {
+    if (!event) return;
+
+    console.log(`[radarflow] Event: ${event.type}`, event);
+
+    if (event.type === "RoundStart" || event.type === "MapChange") {
+        deadPlayers = {};
+    }
+
+    window.dispatchEvent(new CustomEvent("radarflow:event", { detail: event }));
+}
+
 function decompressData(data) {
     try {
         pingTracker.endRequest();
@@ -1491,6 +1816,10 @@ function connect() {
                 socket.send(`ping:0`);
             }, 500);
 
+            if (initialSubscription) {
+                socket.send(`subscribe:${initialSubscription}`);
+            }
+
             if (!fpsStartTime) {
                 requestAnimationFrame(render);
             }
@@ -1515,8 +1844,20 @@ function connect() {
             } else if (typeof event.data === 'string') {
                 try {
                     const jsonData = JSON.parse(event.data);
-                    if (jsonData.action === "toggleMoneyReveal") {
+                    if (jsonData.status === "forbidden") {
+                        console.warn(`[radarflow] Not allowed to ${jsonData.action} with this token`);
+                    } else if (jsonData.action === "subscribe") {
+                        if (jsonData.status !== "success") {
+                            console.error("[radarflow] Invalid subscription:", jsonData.message);
+                        }
+                    } else if (jsonData.action === "toggleMoneyReveal") {
                         document.getElementById("moneyReveal").checked = jsonData.enabled;
+                    } else if (jsonData.action === "setPerspective") {
+                        if (jsonData.status === "success") {
+                            document.getElementById("perspectiveSelect").value = jsonData.perspective.toLowerCase();
+                        }
+                    } else if (jsonData.action === "event") {
+                        processEvent(jsonData.event);
                     } else {
                         processData(jsonData);
                     }
@@ -1595,6 +1936,55 @@ function toggleStats() {
     drawStats = !drawStats;
 }
 
+let scoreboardTimer = null;
+
+function toggleScoreboard() {
+    const panel = document.getElementById("scoreboard");
+    const enabled = document.getElementById("scoreboardCheck").checked;
+
+    panel.style.display = enabled ? "block" : "none";
+    clearInterval(scoreboardTimer);
+
+    if (enabled) {
+        updateScoreboard();
+        scoreboardTimer = setInterval(updateScoreboard, 2000);
+    }
+}
+
+async function updateScoreboard() {
+    try {
+        const query = accessToken ? `?token=${encodeURIComponent(accessToken)}` : "";
+        const response = await fetch(`/api/scoreboard${query}`);
+        if (!response.ok) return;
+
+        renderScoreboard(await response.json());
+    } catch (e) {
+        console.warn("[radarflow] Failed to fetch scoreboard:", e);
+    }
+}
+
+function renderScoreboard(entries) {
+    const panel = document.getElementById("scoreboard");
+    const cell = (value) => `<td>${value ?? "-"}</td>`;
+    const escape = (text) => String(text ?? "").replace(/[&<>"]/g, c => `&#${c.charCodeAt(0)};`);
+
+    let html = "<table><tr><th>Player</th><th>K</th><th>D</th><th>A</th><th>DMG</th><th>HS%</th><th>MVP</th><th>Ping</th><th>Rank</th></tr>";
+
+    for (const entry of entries) {
+        const stats = entry.stats || {};
+        const color = entry.team === "CT" ? "#5d79ae" : entry.team === "T" ? "#de9b35" : "white";
+
+        html += `<tr class="${entry.alive ? "" : "dead"}">`;
+        html += `<td style="color: ${color}">${escape(entry.playerName)}</td>`;
+        html += cell(stats.kills) + cell(stats.deaths) + cell(stats.assists) + cell(stats.damage);
+        html += cell(stats.headshotPercentage !== undefined ? Math.round(stats.headshotPercentage) : undefined);
+        html += cell(stats.mvps) + cell(stats.ping) + cell(stats.rank);
+        html += "</tr>";
+    }
+
+    panel.innerHTML = html + "</table>";
+}
+
 function toggleNames() {
     drawNames = !drawNames;
 }
@@ -1607,6 +1997,14 @@ function toggleRotate() {
     rotateMap = !rotateMap;
 }
 
+function changePerspective() {
+    const select = document.getElementById("perspectiveSelect");
+    if (select && websocket && websocket.readyState === WebSocket.OPEN) {
+        console.log(`[radarflow] Switching perspective to ${select.value}`);
+        websocket.send(`setPerspective:${select.value}`);
+    }
+}
+
 function toggleMoneyReveal() {
     if (websocket && websocket.readyState === WebSocket.OPEN) {
         console.log("[radarflow] Sending toggleMoneyReveal command");